@@ -0,0 +1,148 @@
+// Module: abi_decoder
+//
+// `insert_smart_contract` persists `abi_json` verbatim but nothing was ever decoded from it: logs
+// were stored as raw topics/data and transaction calldata as a raw hex blob. This parses a
+// contract's verified ABI into an `ethabi::Contract` (cached per address, since `logs::insert_log`
+// would otherwise re-parse the same ABI on every single log) and decodes `Log`s and transaction
+// `input` against it into named, typed parameters suitable for a JSONB column.
+
+use ethers::abi::{Abi, RawLog, Token};
+use ethers::prelude::*;
+use ethers::utils::hex;
+use lazy_static::lazy_static;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    /// Parsed `ethabi::Contract`s keyed by address, so a contract's ABI JSON is only ever parsed
+    /// once no matter how many logs/transactions it shows up in.
+    static ref ABI_CACHE: Mutex<HashMap<Address, Arc<Abi>>> = Mutex::new(HashMap::new());
+}
+
+/// One decoded event/function parameter, ready to serialize as a JSONB column alongside the raw
+/// log or transaction.
+#[derive(Debug, Serialize, Clone)]
+pub struct DecodedParam {
+    pub name: String,
+    pub kind: String,
+    pub value: String,
+}
+
+/// Parse `abi_json` into an `ethabi::Contract`, reusing the cached copy for `address` if one was
+/// already parsed.
+fn cached_abi(address: Address, abi_json: &str) -> Option<Arc<Abi>> {
+    if let Some(abi) = ABI_CACHE.lock().unwrap().get(&address) {
+        return Some(abi.clone());
+    }
+
+    let abi: Abi = serde_json::from_str(abi_json).ok()?;
+    let abi = Arc::new(abi);
+    ABI_CACHE.lock().unwrap().insert(address, abi.clone());
+    Some(abi)
+}
+
+/// Decode `log` against `address`'s ABI: match `topics[0]` to `keccak256(event_signature)` to
+/// pick the event, then split the remaining indexed topics plus `data` into named parameters.
+/// Returns the matched event's name alongside its decoded parameters, or `None` if no ABI is
+/// cached/parseable for `address` or no event in it matches the log's signature — this is a
+/// generic fallback for whatever event a contract emits, not just the hardcoded `Transfer` that
+/// [`crate::db::index_transfers`] looks for.
+pub fn decode_log(address: Address, abi_json: &str, log: &Log) -> Option<(String, Vec<DecodedParam>)> {
+    let abi = cached_abi(address, abi_json)?;
+    let signature = *log.topics.first()?;
+    let event = abi.events().find(|event| event.signature() == signature)?;
+
+    let raw_log = RawLog {
+        topics: log.topics.clone(),
+        data: log.data.to_vec(),
+    };
+    let parsed = event.parse_log(raw_log).ok()?;
+
+    let params = parsed
+        .params
+        .into_iter()
+        .map(|param| DecodedParam {
+            name: param.name,
+            kind: token_kind(&param.value).to_string(),
+            value: token_to_string(&param.value),
+        })
+        .collect();
+
+    Some((event.name.clone(), params))
+}
+
+/// Decode `input` against `address`'s ABI: match the leading 4-byte selector to
+/// `keccak256(fn_signature)[..4]` to pick the function, then decode the remaining bytes into its
+/// named argument tuple. Returns the matched function's name alongside its decoded arguments, or
+/// `None` if no ABI is cached/parseable for `address`, `input` is shorter than a selector, or no
+/// function matches the selector.
+pub fn decode_function_input(
+    address: Address,
+    abi_json: &str,
+    input: &Bytes,
+) -> Option<(String, Vec<DecodedParam>)> {
+    if input.len() < 4 {
+        return None;
+    }
+
+    let abi = cached_abi(address, abi_json)?;
+    let selector: [u8; 4] = input[..4].try_into().ok()?;
+    let function = abi
+        .functions()
+        .find(|function| function.short_signature() == selector)?;
+
+    let tokens = function.decode_input(&input[4..]).ok()?;
+    let params = function
+        .inputs
+        .iter()
+        .zip(tokens)
+        .map(|(param, token)| DecodedParam {
+            name: param.name.clone(),
+            kind: token_kind(&token).to_string(),
+            value: token_to_string(&token),
+        })
+        .collect();
+
+    Some((function.name.clone(), params))
+}
+
+/// Serialize decoded parameters to the `Value` that gets bound to a `JSON`/`JSONB` column.
+pub fn params_to_json(params: &[DecodedParam]) -> Value {
+    serde_json::to_value(params).unwrap_or_else(|_| serde_json::json!([]))
+}
+
+/// Human-readable rendering of a decoded `Token`. Arrays/tuples render as a bracketed/parenthesized
+/// list of their own rendering, recursively.
+fn token_to_string(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("0x{:x}", address),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => format!("0x{}", hex::encode(bytes)),
+        Token::Int(value) | Token::Uint(value) => value.to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => value.clone(),
+        Token::FixedArray(tokens) | Token::Array(tokens) => format!(
+            "[{}]",
+            tokens.iter().map(token_to_string).collect::<Vec<_>>().join(",")
+        ),
+        Token::Tuple(tokens) => format!(
+            "({})",
+            tokens.iter().map(token_to_string).collect::<Vec<_>>().join(",")
+        ),
+    }
+}
+
+/// Short type tag stored alongside each decoded value, mirroring Solidity's own type names.
+fn token_kind(token: &Token) -> &'static str {
+    match token {
+        Token::Address(_) => "address",
+        Token::FixedBytes(_) | Token::Bytes(_) => "bytes",
+        Token::Int(_) => "int",
+        Token::Uint(_) => "uint",
+        Token::Bool(_) => "bool",
+        Token::String(_) => "string",
+        Token::FixedArray(_) | Token::Array(_) => "array",
+        Token::Tuple(_) => "tuple",
+    }
+}
@@ -1,11 +1,11 @@
 // Module: db::addresses
 
+use crate::db::{DbTlsConnector, U256Numeric};
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
 use ethers::prelude::*;
-use rust_decimal::prelude::*;
 use std::error::Error;
-use tokio_postgres::{types::ToSql, NoTls};
+use tokio_postgres::types::ToSql;
 use log::{error as log_error, debug};
 
 /// Function to insert an address into the database
@@ -37,17 +37,11 @@ pub async fn insert_address(
     code: Bytes,
     block_number: U64,
     _gas_used: U256, //TODO: handle the gas usage of an address
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), Box<dyn Error>> {
     // Extract relevant data from the address
     let address = format!("0x{:x}", address);
-    let balance = Decimal::from_parts(
-        balance.low_u32() as u32, // lo
-        0,                        // mid
-        0,                        // hi
-        false,                    // negative
-        0,                        // scale
-    );
+    let balance = U256Numeric::from(balance);
     let nonce = nonce.as_u64() as i32;
     let transaction_count = transaction_count.as_u64() as i32;
     let storage = format!("0x{:x}", storage);
@@ -1,13 +1,18 @@
 // Module: db::blocks
 
+use crate::db::{DbTlsConnector, U256Numeric};
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
 use ethers::prelude::*;
-use rust_decimal::prelude::*;
+use futures::pin_mut;
+use rust_decimal::Decimal;
 use serde_json;
+use std::collections::HashSet;
+use std::env;
 use std::error::Error;
-use tokio_postgres::{types::ToSql, NoTls};
-use log::{error as log_error, debug};
+use std::sync::Arc;
+use tokio_postgres::{binary_copy::BinaryCopyInWriter, types::ToSql, types::Type};
+use log::{error as log_error, debug, warn};
 
 /// Function to insert a block into the database
 /// Database schema:
@@ -34,10 +39,18 @@ use log::{error as log_error, debug};
 /// "lastUpdated" timestamp default current_timestamp
 /// );
 ///
+/// Detects a chain reorg before writing `block`'s own row (see [`detect_and_handle_reorg`]):
+/// on a detected fork, the divergent range is already deleted by the time the `INSERT` below
+/// runs, and the conflict clause is `DO UPDATE` rather than `DO NOTHING` so that re-enacting a
+/// block number that got rolled back (and is now being re-inserted on the canonical chain)
+/// overwrites the stale row instead of being silently ignored.
 pub async fn insert_block(
     block: Block<H256>,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
-) -> Result<(), Box<dyn Error>> {
+    ws_client: Arc<Provider<Ws>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<U64>, Box<dyn Error>> {
+    let divergent_number = detect_and_handle_reorg(&block, ws_client, db_pool.clone()).await?;
+
     debug!(
         "Inserting block {} into database",
         block.number.unwrap().to_string()
@@ -54,15 +67,11 @@ pub async fn insert_block(
     let state_root = format!("0x{:x}", block.state_root);
     let miner = format!("0x{:x}", block.author.unwrap());
     let difficulty = block.difficulty.as_u64() as i64;
-    // let total_difficulty = block.total_difficulty.map(|d| Decimal::from(d.as_u64() as i64)).unwrap_or_default();
-    let total_difficulty = block
-        .total_difficulty
-        .map(|u256| Decimal::from(u256.as_u128()))
-        .unwrap_or(Decimal::new(0, 0));
+    let total_difficulty = U256Numeric::from(block.total_difficulty.unwrap_or_default());
     let size = block.size.unwrap().as_u32() as i32;
     let extra_data = format!("{:x}", block.extra_data);
-    let gas_limit = Decimal::from(block.gas_limit.as_u128() as i64);
-    let gas_used = Decimal::from(block.gas_used.as_u128() as i64);
+    let gas_limit = U256Numeric::from(block.gas_limit);
+    let gas_used = U256Numeric::from(block.gas_used);
     let timestamp = block.timestamp.as_u64() as i32;
     let transactions_count = block.transactions.len() as i32;
     let transactions_ids = serde_json::to_value(&block.transactions).unwrap();
@@ -74,7 +83,25 @@ pub async fn insert_block(
                             "stateRoot", "miner", "difficulty", "totalDifficulty", "size", "extraData", "gasLimit",
                             "gasUsed", "timestamp", "transactionsCount", "transactions_ids", "uncles", "insertedAt")
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, NOW())
-        ON CONFLICT ("number") DO NOTHING;
+        ON CONFLICT ("number") DO UPDATE SET
+        "hash" = EXCLUDED."hash",
+        "parentHash" = EXCLUDED."parentHash",
+        "nonce" = EXCLUDED."nonce",
+        "sha3Uncles" = EXCLUDED."sha3Uncles",
+        "logsBloom" = EXCLUDED."logsBloom",
+        "transactionsRoot" = EXCLUDED."transactionsRoot",
+        "stateRoot" = EXCLUDED."stateRoot",
+        "miner" = EXCLUDED."miner",
+        "difficulty" = EXCLUDED."difficulty",
+        "totalDifficulty" = EXCLUDED."totalDifficulty",
+        "size" = EXCLUDED."size",
+        "extraData" = EXCLUDED."extraData",
+        "gasLimit" = EXCLUDED."gasLimit",
+        "gasUsed" = EXCLUDED."gasUsed",
+        "timestamp" = EXCLUDED."timestamp",
+        "transactionsCount" = EXCLUDED."transactionsCount",
+        "transactions_ids" = EXCLUDED."transactions_ids",
+        "uncles" = EXCLUDED."uncles";
     "#;
     // Prepare the statement
     let db_client = db_pool.get().await.map_err(|e| {
@@ -115,7 +142,7 @@ pub async fn insert_block(
     match result {
         Ok(_) => {
             debug!("Block {} inserted successfully", number);
-            Ok(())
+            Ok(divergent_number)
         }
         Err(err) => {
             log_error!("Error inserting block {}: {}", number, err);
@@ -123,3 +150,407 @@ pub async fn insert_block(
         }
     }
 }
+
+/// Compare `block`'s `parent_hash` against the stored hash of block `number - 1` (an
+/// OpenEthereum `TreeRoute`-style check between the stored tip and the incoming block). On a
+/// mismatch, walk backwards from both the stored chain and the canonical chain (capped at
+/// `REORG_MAX_DEPTH`, default 64) until a common ancestor is found, then roll back every row
+/// from that divergent height onward in a single transaction (see [`rollback_from_block`]) —
+/// the retracted range. [`insert_block`] inserting `block` right after is the enacted range's
+/// first member; the rest of the enacted range is left for the caller to re-index forward.
+///
+/// Returns the divergent height on a detected reorg, or `None` if `block` extends the stored
+/// chain with no reorg.
+async fn detect_and_handle_reorg(
+    block: &Block<H256>,
+    ws_client: Arc<Provider<Ws>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<U64>, Box<dyn Error>> {
+    let block_number = match block.number {
+        Some(number) if number.as_u64() > 0 => number,
+        _ => return Ok(None), // genesis block has no parent to check
+    };
+    let parent_number = U64::from(block_number.as_u64() - 1);
+
+    let stored_parent_hash = match get_block_hash(parent_number, db_pool.clone()).await? {
+        Some(hash) => hash,
+        None => return Ok(None), // parent not indexed yet; nothing to reconcile
+    };
+    let mut canonical_hash = format!("0x{:x}", block.parent_hash);
+
+    if stored_parent_hash == canonical_hash {
+        return Ok(None); // parent-hash linkage holds, no reorg
+    }
+
+    warn!(
+        "Reorg detected at block {}: stored block {} hash {} does not match canonical parent hash {}",
+        block_number, parent_number, stored_parent_hash, canonical_hash
+    );
+
+    let max_depth = env::var("REORG_MAX_DEPTH")
+        .unwrap_or_else(|_| "64".to_string())
+        .parse::<u64>()
+        .unwrap_or(64);
+
+    // Walk backwards from the parent until the stored hash at some height matches the canonical
+    // chain's hash at that height, i.e. until we find the common ancestor.
+    let mut divergent_number = parent_number;
+    for _ in 0..max_depth {
+        if divergent_number.as_u64() == 0 {
+            break;
+        }
+        let ancestor_number = U64::from(divergent_number.as_u64() - 1);
+        let ancestor_block = match ws_client.get_block(ancestor_number).await {
+            Ok(Some(ancestor_block)) => ancestor_block,
+            _ => {
+                return Err(format!(
+                    "Failed to fetch canonical block {} while walking back reorg",
+                    ancestor_number
+                )
+                .into())
+            }
+        };
+        canonical_hash = format!("0x{:x}", ancestor_block.hash.unwrap());
+
+        match get_block_hash(ancestor_number, db_pool.clone()).await? {
+            Some(stored_hash) if stored_hash == canonical_hash => break, // common ancestor found
+            _ => divergent_number = ancestor_number,
+        }
+    }
+
+    rollback_from_block(divergent_number, db_pool).await?;
+    Ok(Some(divergent_number))
+}
+
+/// Bulk-insert a batch of blocks using Postgres binary `COPY`.
+///
+/// `insert_block` issues one prepared `execute()` per row, which caps throughput during
+/// backfill. This streams the whole batch into a per-connection temp table via
+/// `COPY ... FROM STDIN BINARY` and folds it into `blocks` with a single
+/// `INSERT ... SELECT ... ON CONFLICT DO NOTHING`, since `COPY` itself cannot express the
+/// upsert clause. Intended to be called once per `index_blocks` batch instead of calling
+/// `insert_block` per row.
+pub async fn insert_blocks_bulk(
+    blocks: Vec<Block<H256>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    if blocks.is_empty() {
+        return Ok(());
+    }
+
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+
+    let transaction = db_client.transaction().await?;
+
+    // Stage the batch in an UNLOGGED-equivalent temp table so we skip WAL overhead for data
+    // that only needs to survive long enough to be folded into "blocks".
+    transaction
+        .batch_execute(
+            r#"
+            CREATE TEMP TABLE temp_blocks (
+                "number" BIGINT, "hash" VARCHAR(66), "parentHash" VARCHAR(66), "nonce" VARCHAR(18),
+                "sha3Uncles" TEXT, "logsBloom" TEXT, "transactionsRoot" VARCHAR(66),
+                "stateRoot" VARCHAR(66), "miner" VARCHAR(42), "difficulty" BIGINT,
+                "totalDifficulty" NUMERIC(50), "size" INT, "extraData" VARCHAR(66),
+                "gasLimit" NUMERIC(100), "gasUsed" NUMERIC(100), "timestamp" INT,
+                "transactionsCount" INT, "transactions_ids" JSON, "uncles" JSON
+            ) ON COMMIT DROP;
+            "#,
+        )
+        .await?;
+
+    let sink = transaction
+        .copy_in(
+            r#"COPY temp_blocks ("number", "hash", "parentHash", "nonce", "sha3Uncles",
+                "logsBloom", "transactionsRoot", "stateRoot", "miner", "difficulty",
+                "totalDifficulty", "size", "extraData", "gasLimit", "gasUsed", "timestamp",
+                "transactionsCount", "transactions_ids", "uncles") FROM STDIN BINARY"#,
+        )
+        .await?;
+    let writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::INT8,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::TEXT,
+            Type::TEXT,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::INT8,
+            Type::NUMERIC,
+            Type::INT4,
+            Type::VARCHAR,
+            Type::NUMERIC,
+            Type::NUMERIC,
+            Type::INT4,
+            Type::INT4,
+            Type::JSON,
+            Type::JSON,
+        ],
+    );
+    pin_mut!(writer);
+
+    for block in &blocks {
+        let number = block.number.unwrap().as_u64() as i64;
+        let hash = format!("0x{:x}", block.hash.unwrap());
+        let parent_hash = format!("0x{:x}", block.parent_hash);
+        let nonce = format!("0x{:x}", block.nonce.unwrap());
+        let sha3_uncles = serde_json::to_value(&block.uncles).unwrap().to_string();
+        let logs_bloom = format!("0x{:x}", block.logs_bloom.unwrap());
+        let transactions_root = format!("0x{:x}", block.transactions_root);
+        let state_root = format!("0x{:x}", block.state_root);
+        let miner = format!("0x{:x}", block.author.unwrap());
+        let difficulty = block.difficulty.as_u64() as i64;
+        let total_difficulty = U256Numeric::from(block.total_difficulty.unwrap_or_default());
+        let size = block.size.unwrap().as_u32() as i32;
+        let extra_data = format!("{:x}", block.extra_data);
+        let gas_limit = U256Numeric::from(block.gas_limit);
+        let gas_used = U256Numeric::from(block.gas_used);
+        let timestamp = block.timestamp.as_u64() as i32;
+        let transactions_count = block.transactions.len() as i32;
+        let transactions_ids = serde_json::to_value(&block.transactions).unwrap();
+        let uncles = serde_json::to_value(&block.uncles).unwrap();
+
+        writer
+            .as_mut()
+            .write(&[
+                &number,
+                &hash,
+                &parent_hash,
+                &nonce,
+                &sha3_uncles,
+                &logs_bloom,
+                &transactions_root,
+                &state_root,
+                &miner,
+                &difficulty,
+                &total_difficulty,
+                &size,
+                &extra_data,
+                &gas_limit,
+                &gas_used,
+                &timestamp,
+                &transactions_count,
+                &transactions_ids,
+                &uncles,
+            ])
+            .await?;
+    }
+    writer.finish().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            INSERT INTO blocks ("number", "hash", "parentHash", "nonce", "sha3Uncles",
+                "logsBloom", "transactionsRoot", "stateRoot", "miner", "difficulty",
+                "totalDifficulty", "size", "extraData", "gasLimit", "gasUsed", "timestamp",
+                "transactionsCount", "transactions_ids", "uncles", "insertedAt")
+            SELECT *, NOW() FROM temp_blocks
+            ON CONFLICT ("number") DO NOTHING;
+            "#,
+        )
+        .await?;
+
+    transaction.commit().await?;
+    debug!("Bulk-inserted {} blocks", blocks.len());
+    Ok(())
+}
+
+/// Fetch the stored hash of block `number`, if it has been indexed.
+pub async fn get_block_hash(
+    number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let row = db_client
+        .query_opt(
+            r#"SELECT "hash" FROM blocks WHERE "number" = $1"#,
+            &[&(number.as_u64() as i64)],
+        )
+        .await?;
+    Ok(row.map(|row| row.get::<_, String>("hash")))
+}
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Roll back chain state for a detected reorg: unwinds the `token_balances` deltas and
+/// `holderCount`s that the reorged-out ERC-20 transfers applied, deletes every row in `logs`,
+/// `token_transfers`, `token_approvals`, `nft_token_owners`, and `transactions` from
+/// `from_number` (inclusive) onward, then `blocks` itself, all in a single transaction so a
+/// rollback can't be observed half-applied. `transactions_receipts`, `contracts`, and `addresses`
+/// all declare `blockNumber` as `FOREIGN KEY ... ON DELETE CASCADE` against `blocks`, so deleting
+/// the `blocks` row cascades those automatically; `logs`, `token_transfers`, `token_approvals`,
+/// and `nft_token_owners` have no such FK (they're keyed on their own columns, not `blocks`
+/// directly), so each needs an explicit delete here or a reorged-out block's rows would linger
+/// forever as orphans. The caller is expected to re-index forward from `from_number` afterwards,
+/// which rebuilds `token_balances`/`holderCount`/`nft_token_owners` from the replayed transfers —
+/// this only has to undo what's being deleted, not reconstruct the pre-reorg state itself.
+pub async fn rollback_from_block(
+    from_number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let transaction = db_client.transaction().await?;
+    let from_number = from_number.as_u64() as i64;
+
+    // Reverse every reorged-out ERC20 transfer's `token_balances` delta before the transfers
+    // themselves are deleted below, mirroring `tokens::insert_erc20_transfer`'s forward delta
+    // with the sign flipped (credit the sender back, debit the receiver back).
+    let reorged_transfers = transaction
+        .query(
+            r#"SELECT "contractAddress", "fromAddress", "toAddress", "amount" FROM token_transfers WHERE "blockNumber" >= $1 AND "standard" = 'ERC20'"#,
+            &[&from_number],
+        )
+        .await?;
+
+    let mut affected_contracts = HashSet::new();
+    for row in &reorged_transfers {
+        let contract_address: String = row.get("contractAddress");
+        let from_address: String = row.get("fromAddress");
+        let to_address: String = row.get("toAddress");
+        let amount: Decimal = row.get("amount");
+
+        affected_contracts.insert(contract_address.clone());
+
+        for (holder_address, delta) in [(&from_address, amount), (&to_address, -amount)] {
+            if holder_address == ZERO_ADDRESS {
+                continue;
+            }
+            transaction
+                .execute(
+                    r#"
+                    INSERT INTO token_balances ("contractAddress", "holderAddress", "balance")
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT ("contractAddress", "holderAddress")
+                    DO UPDATE SET
+                    "balance" = token_balances."balance" + EXCLUDED."balance",
+                    "lastUpdated" = NOW()
+                    "#,
+                    &[&contract_address, holder_address, &delta],
+                )
+                .await?;
+        }
+    }
+
+    for contract_address in &affected_contracts {
+        let holder_count: i64 = transaction
+            .query_one(
+                r#"SELECT COUNT(*) FROM token_balances WHERE "contractAddress" = $1 AND "balance" <> 0"#,
+                &[contract_address],
+            )
+            .await?
+            .get(0);
+        transaction
+            .execute(
+                r#"UPDATE tokens SET "holderCount" = $1 WHERE "address" = $2"#,
+                &[&(holder_count as i32), contract_address],
+            )
+            .await?;
+    }
+
+    transaction
+        .execute(
+            r#"DELETE FROM logs WHERE "blockNumber" >= $1"#,
+            &[&from_number],
+        )
+        .await?;
+    transaction
+        .execute(
+            r#"DELETE FROM token_transfers WHERE "blockNumber" >= $1"#,
+            &[&from_number],
+        )
+        .await?;
+    transaction
+        .execute(
+            r#"DELETE FROM token_approvals WHERE "blockNumber" >= $1"#,
+            &[&from_number],
+        )
+        .await?;
+    transaction
+        .execute(
+            r#"DELETE FROM nft_token_owners WHERE "updatedAtBlock" >= $1"#,
+            &[&from_number],
+        )
+        .await?;
+    transaction
+        .execute(
+            r#"DELETE FROM transactions WHERE "blockNumber" >= $1"#,
+            &[&from_number],
+        )
+        .await?;
+    transaction
+        .execute(r#"DELETE FROM blocks WHERE "number" >= $1"#, &[&from_number])
+        .await?;
+
+    transaction.commit().await?;
+    warn!("Rolled back chain state from block {} due to reorg", from_number);
+    Ok(())
+}
+
+/// Lowest and highest block `"number"` currently stored in `blocks`, or `None` if the table is
+/// empty. `run_backfill` uses this as the range to scan for gaps.
+pub async fn get_block_range(
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<(U64, U64)>, Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let row = db_client
+        .query_one(r#"SELECT MIN("number") AS "min", MAX("number") AS "max" FROM blocks"#, &[])
+        .await?;
+    match (row.get::<_, Option<i64>>("min"), row.get::<_, Option<i64>>("max")) {
+        (Some(min), Some(max)) => Ok(Some((U64::from(min as u64), U64::from(max as u64)))),
+        _ => Ok(None),
+    }
+}
+
+/// Find every contiguous run of missing block numbers in `[from_number, to_number]`, using the
+/// standard "gaps and islands" window-function pattern: pair each stored block with the next one
+/// (via `LEAD`), and any pair further apart than 1 has a gap between them.
+pub async fn find_gaps(
+    from_number: U64,
+    to_number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Vec<(U64, U64)>, Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let rows = db_client
+        .query(
+            r#"
+            SELECT "number" + 1 AS "gapStart", "nextNumber" - 1 AS "gapEnd" FROM (
+                SELECT "number", LEAD("number") OVER (ORDER BY "number") AS "nextNumber"
+                FROM blocks
+                WHERE "number" BETWEEN $1 AND $2
+            ) numbered
+            WHERE "nextNumber" - "number" > 1
+            ORDER BY "gapStart"
+            "#,
+            &[&(from_number.as_u64() as i64), &(to_number.as_u64() as i64)],
+        )
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            (
+                U64::from(row.get::<_, i64>("gapStart") as u64),
+                U64::from(row.get::<_, i64>("gapEnd") as u64),
+            )
+        })
+        .collect())
+}
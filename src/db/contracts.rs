@@ -1,7 +1,7 @@
 // Module: db::contracts
 
 use crate::{
-    db::{logs, tokens},
+    db::{logs, tokens, DbTlsConnector},
     indexer_types,
 };
 use bb8::Pool;
@@ -10,7 +10,7 @@ use ethers::prelude::*;
 use log::{debug, warn, error as log_error};
 use serde_json;
 use std::{error::Error, sync::Arc};
-use tokio_postgres::{types::ToSql, NoTls};
+use tokio_postgres::types::ToSql;
 
 /// Function to insert smart contract information into the database
 /// Particularity is that we need the ws_client to get the smart contract data if we have the ABI.
@@ -41,7 +41,7 @@ pub async fn insert_smart_contract(
     transaction_receipt: TransactionReceipt,
     code: Bytes,
     verified_sc_data: indexer_types::ContractInfo,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
     ws_client: Arc<Provider<Ws>>,
 ) -> Result<(), Box<dyn Error>> {
     // It is possible that the verified_source_code is empty
@@ -184,16 +184,48 @@ pub async fn insert_smart_contract(
             debug!("Smart contract {} inserted/updated successfully", address);
             if !verified_sc_data.is_null() {
                 debug!("Detected contract type: {}", contract_type);
-                if contract_type == "ERC20" {
-                    tokens::insert_erc20_token(
-                        transaction_receipt.contract_address.unwrap(),
-                        verified_sc_data.clone(),
-                        transaction_receipt.block_number.unwrap(),
-                        db_pool.clone(),
-                        ws_client.clone(),
-                    )
-                    .await?;
+                let is_token_contract = match contract_type.as_str() {
+                    "ERC20" => {
+                        tokens::insert_erc20_token(
+                            transaction_receipt.contract_address.unwrap(),
+                            verified_sc_data.clone(),
+                            transaction_receipt.block_number.unwrap(),
+                            db_pool.clone(),
+                            ws_client.clone(),
+                        )
+                        .await?;
+                        true
+                    }
+                    "ERC721" => {
+                        tokens::insert_erc721_token(
+                            transaction_receipt.contract_address.unwrap(),
+                            verified_sc_data.clone(),
+                            transaction_receipt.block_number.unwrap(),
+                            db_pool.clone(),
+                            ws_client.clone(),
+                        )
+                        .await?;
+                        true
+                    }
+                    "ERC1155" => {
+                        tokens::insert_erc1155_token(
+                            transaction_receipt.contract_address.unwrap(),
+                            verified_sc_data.clone(),
+                            transaction_receipt.block_number.unwrap(),
+                            db_pool.clone(),
+                            ws_client.clone(),
+                        )
+                        .await?;
+                        true
+                    }
+                    _ => {
+                        //TODO: Handle other contract types (e.g. ERC777)
+                        warn!("Contract type '{}' is not supported yet", contract_type.to_string());
+                        false
+                    }
+                };
 
+                if is_token_contract {
                     //trick to process logs from constructor, that seems to not show up in the receipts logs
                     let filter_by_block =
                         Filter::new().from_block(transaction_receipt.block_number.unwrap());
@@ -208,9 +240,6 @@ pub async fn insert_smart_contract(
                     for log in constructor_logs {
                         logs::insert_log(log, db_pool.clone(), ws_client.clone()).await?;
                     }
-                } else {
-                    //TODO: Handle other contract types
-                    warn!("Contract type '{}' is not supported yet", contract_type.to_string());
                 }
             }
             Ok(())
@@ -225,3 +254,57 @@ pub async fn insert_smart_contract(
         }
     }
 }
+
+/// Look up the verified ABI JSON stored for `address`, if any. Used by `db::decoded_logs` and
+/// [`crate::db::transactions::insert_transaction`] to decode
+/// that contract's logs/calldata; returns `None` for an unindexed or unverified address (empty
+/// `"[]"` ABI) rather than an empty string, so callers can skip decoding with a single `if let`.
+pub async fn get_abi_json(
+    address: Address,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let address = format!("0x{:x}", address);
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let row = db_client
+        .query_opt(r#"SELECT "abi" FROM contracts WHERE "address" = $1"#, &[&address])
+        .await?;
+
+    let abi = match row {
+        Some(row) => row.get::<_, serde_json::Value>("abi"),
+        None => return Ok(None),
+    };
+
+    match abi {
+        serde_json::Value::Array(ref items) if items.is_empty() => Ok(None),
+        serde_json::Value::Null => Ok(None),
+        abi => Ok(Some(abi.to_string())),
+    }
+}
+
+/// Look up the `contractType` ERC-165/function-selector detection previously recorded for
+/// `address` (see [`indexer_types::ContractType::detect_contract_type`]), or `None` if the
+/// contract hasn't been indexed or was never typed.
+pub async fn get_contract_type(
+    address: Address,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<String>, Box<dyn Error>> {
+    let address = format!("0x{:x}", address);
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let row = db_client
+        .query_opt(r#"SELECT "contractType" FROM contracts WHERE "address" = $1"#, &[&address])
+        .await?;
+
+    match row {
+        Some(row) => match row.get::<_, Option<String>>("contractType") {
+            Some(s) if !s.is_empty() => Ok(Some(s)),
+            _ => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
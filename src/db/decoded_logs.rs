@@ -0,0 +1,191 @@
+// Module: db::decoded_logs
+use crate::abi_decoder;
+use crate::db::{contracts, DbTlsConnector};
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use ethers::prelude::*;
+use futures::pin_mut;
+use log::{debug, error as log_error};
+use std::error::Error;
+use tokio_postgres::{binary_copy::BinaryCopyInWriter, types::ToSql, types::Type};
+
+/// Decode `log` against its emitting contract's verified ABI and, on a match, persist the event
+/// name and decoded parameters into `decoded_logs`.
+/// Database schema:
+/// CREATE TABLE decoded_logs (
+///     "transactionHash" VARCHAR(66) NOT NULL,
+///     "blockHash" VARCHAR(66) NOT NULL,
+///     "index" integer NOT NULL,
+///     "eventName" VARCHAR(255) NOT NULL,
+///     "decodedParams" JSON NOT NULL,
+///     "insertedAt" timestamp,
+///     "updatedAt" timestamp default current_timestamp,
+///     CONSTRAINT decoded_logs_pkey PRIMARY KEY ("transactionHash", "blockHash", "index"),
+///     FOREIGN KEY ("transactionHash", "blockHash", "index") REFERENCES logs("transactionHash", "blockHash", "index") ON DELETE CASCADE
+/// );
+///
+/// This is the generic counterpart to `logs` itself: `logs` stores every raw log whether or not
+/// it could be decoded, while `decoded_logs` only gets a row when `topics[0]` matches an event in
+/// the emitting contract's verified ABI (see [`abi_decoder::decode_log`]) and
+/// [`crate::token_filter::should_index`] allows the address. A row here is one event out of
+/// however many the ABI declares — not just `Transfer`, which [`crate::db::index_transfers`]
+/// already handles separately via its own signature-based path that doesn't need a verified ABI.
+pub async fn insert_decoded_log(
+    log: &Log,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    if !crate::token_filter::should_index(log.address) {
+        return Ok(());
+    }
+
+    let Some((event_name, decoded_params)) = decode(log, db_pool.clone()).await else {
+        return Ok(());
+    };
+
+    let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+    let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+    let index = log.log_index.unwrap().as_u64() as i32;
+
+    let query = r#"
+        INSERT INTO decoded_logs
+        ("transactionHash", "blockHash", "index", "eventName", "decodedParams", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "index")
+        DO UPDATE SET
+        "eventName" = EXCLUDED."eventName",
+        "decodedParams" = EXCLUDED."decodedParams"
+    "#;
+
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let statement = db_client
+        .prepare(query)
+        .await
+        .expect("Failed to prepare statement");
+    let params: [&(dyn ToSql + Sync); 5] = [
+        &transaction_hash,
+        &block_hash,
+        &index,
+        &event_name,
+        &decoded_params,
+    ];
+
+    match db_client.execute(&statement, &params).await {
+        Ok(_) => {
+            debug!("Inserted decoded log: {} ({})", transaction_hash, event_name);
+            Ok(())
+        }
+        Err(e) => {
+            log_error!("Error inserting decoded log for {}: {}", transaction_hash, e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+/// Bulk counterpart to [`insert_decoded_log`], mirroring `logs::insert_logs_bulk`'s
+/// `COPY`-into-temp-table-then-`INSERT ... SELECT` shape. Logs that don't decode to an event
+/// (no verified ABI, no matching `topics[0]`, or filtered out by `token_filter`) simply don't get
+/// a row — there's no "undecoded" placeholder to write, unlike `logs` which stores every log
+/// regardless.
+pub async fn insert_decoded_logs_bulk(
+    logs: &[Log],
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut decoded_rows = Vec::new();
+    for log in logs {
+        if !crate::token_filter::should_index(log.address) {
+            continue;
+        }
+        if let Some((event_name, decoded_params)) = decode(log, db_pool.clone()).await {
+            decoded_rows.push((log, event_name, decoded_params));
+        }
+    }
+
+    if decoded_rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+
+    let transaction = db_client.transaction().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            CREATE TEMP TABLE temp_decoded_logs (
+                "transactionHash" VARCHAR(66), "blockHash" VARCHAR(66), "index" INT,
+                "eventName" VARCHAR(255), "decodedParams" JSON
+            ) ON COMMIT DROP;
+            "#,
+        )
+        .await?;
+
+    let sink = transaction
+        .copy_in(
+            r#"COPY temp_decoded_logs ("transactionHash", "blockHash", "index", "eventName",
+                "decodedParams") FROM STDIN BINARY"#,
+        )
+        .await?;
+    let writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::INT4,
+            Type::VARCHAR,
+            Type::JSON,
+        ],
+    );
+    pin_mut!(writer);
+
+    for (log, event_name, decoded_params) in &decoded_rows {
+        let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+        let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+        let index = log.log_index.unwrap().as_u64() as i32;
+
+        writer
+            .as_mut()
+            .write(&[&transaction_hash, &block_hash, &index, event_name, decoded_params])
+            .await?;
+    }
+    writer.finish().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            INSERT INTO decoded_logs ("transactionHash", "blockHash", "index", "eventName",
+                "decodedParams", "insertedAt")
+            SELECT *, NOW() FROM temp_decoded_logs
+            ON CONFLICT ("transactionHash", "blockHash", "index")
+            DO UPDATE SET
+            "eventName" = EXCLUDED."eventName",
+            "decodedParams" = EXCLUDED."decodedParams";
+            "#,
+        )
+        .await?;
+
+    transaction.commit().await?;
+    debug!("Bulk-inserted {} decoded logs", decoded_rows.len());
+    Ok(())
+}
+
+/// Shared ABI-lookup-then-decode step for both the single-row and bulk insert paths.
+async fn decode(
+    log: &Log,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Option<(String, serde_json::Value)> {
+    match contracts::get_abi_json(log.address, db_pool).await {
+        Ok(Some(abi_json)) => abi_decoder::decode_log(log.address, &abi_json, log)
+            .map(|(name, params)| (name, abi_decoder::params_to_json(&params))),
+        Ok(None) => None,
+        Err(e) => {
+            log_error!("Error fetching ABI for {:?}: {}", log.address, e);
+            None
+        }
+    }
+}
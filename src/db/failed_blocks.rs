@@ -0,0 +1,101 @@
+// Module: db::failed_blocks
+//
+// Dead-letter queue for blocks that exhausted `retry::call_with_retry` inside `index_block`.
+// Rather than losing them to a log line, their number (and the error that finally gave up) is
+// kept here so `Indexer::run_retry_failed` can sweep back over them once the endpoint or
+// database recovers — the same "don't trust memory across a restart" idea as `db::state`'s
+// cursor, just for the blocks that fell through rather than the ones that succeeded.
+//
+// Database schema:
+// CREATE TABLE failed_blocks (
+//     "number" BIGINT PRIMARY KEY,
+//     "error" TEXT NOT NULL,
+//     "failedAt" timestamp default current_timestamp
+// );
+use crate::db::DbTlsConnector;
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use ethers::prelude::*;
+use log::error as log_error;
+use std::error::Error;
+
+/// Ensure the `failed_blocks` table exists. Called from `init_db` alongside `indexer_state`.
+pub async fn ensure_failed_blocks_table(
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    db_client
+        .batch_execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS failed_blocks (
+                "number" BIGINT PRIMARY KEY,
+                "error" TEXT NOT NULL,
+                "failedAt" timestamp default current_timestamp
+            );
+            "#,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Record `block_number` as failed with `error`, upserting so a block that keeps failing just
+/// refreshes its row instead of accumulating duplicates.
+pub async fn record_failed_block(
+    block_number: U64,
+    error: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    db_client
+        .execute(
+            r#"
+            INSERT INTO failed_blocks ("number", "error", "failedAt")
+            VALUES ($1, $2, NOW())
+            ON CONFLICT ("number") DO UPDATE SET
+            "error" = EXCLUDED."error",
+            "failedAt" = NOW()
+            "#,
+            &[&(block_number.as_u64() as i64), &error],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Remove `block_number` from the dead-letter queue, e.g. after `run_retry_failed` successfully
+/// re-indexes it.
+pub async fn clear_failed_block(
+    block_number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    db_client
+        .execute(r#"DELETE FROM failed_blocks WHERE "number" = $1"#, &[&(block_number.as_u64() as i64)])
+        .await?;
+    Ok(())
+}
+
+/// Every block number currently in the dead-letter queue, lowest first.
+pub async fn list_failed_blocks(
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Vec<U64>, Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let rows = db_client
+        .query(r#"SELECT "number" FROM failed_blocks ORDER BY "number""#, &[])
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| U64::from(row.get::<_, i64>("number") as u64))
+        .collect())
+}
@@ -1,14 +1,12 @@
 // Module: db::logs
-use crate::db::{self, tokens};
-use crate::indexer_types;
+use crate::db::{decoded_logs, DbTlsConnector};
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
-use ethers::utils::keccak256;
-use ethers::{abi::Abi, prelude::*};
-use ethers_contract::Contract;
-use log::{debug, error as log_error, warn};
+use ethers::prelude::*;
+use futures::pin_mut;
+use log::{debug, error as log_error};
 use std::{error::Error, sync::Arc};
-use tokio_postgres::{types::ToSql, NoTls};
+use tokio_postgres::{binary_copy::BinaryCopyInWriter, types::ToSql, types::Type};
 
 /// Function to insert a log into the database
 /// Database schema:
@@ -28,9 +26,13 @@ use tokio_postgres::{types::ToSql, NoTls};
 ///     "updatedAt" timestamp default current_timestamp,
 ///     CONSTRAINT logs_pkey PRIMARY KEY ("transactionHash", "blockHash", "index")
 /// );
+///
+/// The event name and ABI-decoded parameters for whichever logs turn out to be decodable live in
+/// their own `decoded_logs` table (see [`decoded_logs::insert_decoded_log`]) rather than as
+/// columns here, so a raw log still gets a row even when decoding fails or isn't attempted.
 pub async fn insert_log(
     log: Log,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
     _ws_client: Arc<Provider<Ws>>,
 ) -> Result<(), Box<dyn Error>> {
     debug!("Inserting log: {:?}", log.address.to_string());
@@ -62,11 +64,11 @@ pub async fn insert_log(
 
     // Build the SQL query
     let query = r#"
-        INSERT INTO logs 
-        ("data", "index", "type", "firstTopic", "secondTopic", "thirdTopic", "fourthTopic", "address", "transactionHash", "blockHash", "blockNumber", "insertedAt") 
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW()) 
-        ON CONFLICT ("transactionHash", "blockHash", "index") 
-        DO UPDATE SET 
+        INSERT INTO logs
+        ("data", "index", "type", "firstTopic", "secondTopic", "thirdTopic", "fourthTopic", "address", "transactionHash", "blockHash", "blockNumber", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "index")
+        DO UPDATE SET
         "data" = EXCLUDED."data",
         "type" = EXCLUDED."type",
         "firstTopic" = EXCLUDED."firstTopic",
@@ -106,91 +108,134 @@ pub async fn insert_log(
     match result {
         Ok(_) => {
             debug!("Inserted log: {}", address);
+            // `decoded_logs` has a FOREIGN KEY on this row, so it can only be written once the
+            // parent `logs` insert above has actually gone through.
+            if let Err(e) = decoded_logs::insert_decoded_log(&log, db_pool.clone()).await {
+                log_error!("Error inserting decoded log for {:?}: {}", log.address, e);
+            }
+            Ok(())
         }
         Err(e) => {
             log_error!("Error inserting log: {}", address);
             log_error!("Error: {}", e);
-            return Err(Box::new(e));
+            Err(Box::new(e))
         }
     }
+}
 
-    // From here on: Detect token transfer in logs and store token transfer in DB
-
-    // Get the ABI for the contract address
-    let abi: serde_json::Value =
-        match db::get_abi_by_address(address.clone(), db_pool.clone()).await {
-            Ok(abi) => abi,
-            Err(e) => {
-                // if error is "No ABI" then return ok
-                if e.to_string() == "No ABI" {
-                    return Ok(());
-                }
-                return Err(e);
-            }
-        };
-    debug!("ABI found for address: {}", address);
-
-    // Parse the JSON ABI
-    let contract_abi: Abi = serde_json::from_value(abi.clone()).expect("Failed to parse ABI");
-    let contract = Contract::new(
-        log.clone().address.clone(),
-        contract_abi,
-        _ws_client.clone(),
+/// Bulk-insert a batch of logs using Postgres binary `COPY`.
+///
+/// Mirrors [`crate::db::insert_blocks_bulk`]/[`crate::db::insert_transactions_bulk`]: rows are
+/// streamed into a per-connection temp table via `COPY ... FROM STDIN BINARY` and folded into
+/// `logs` with a single `INSERT ... SELECT ... ON CONFLICT DO NOTHING`, so a whole `index_blocks`
+/// batch's logs flush in one round trip instead of one `execute()` per log. Decoding and
+/// persisting each log's event into `decoded_logs` is delegated to
+/// [`decoded_logs::insert_decoded_logs_bulk`], which runs its own `get_abi_json` lookups ahead of
+/// its own COPY — but not until after this function's `logs` transaction has committed, since
+/// `decoded_logs` has a FOREIGN KEY on the row it references.
+pub async fn insert_logs_bulk(
+    logs: Vec<Log>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    if logs.is_empty() {
+        return Ok(());
+    }
+
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+
+    let transaction = db_client.transaction().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            CREATE TEMP TABLE temp_logs (
+                "data" BYTEA, "index" INT, "type" VARCHAR(255), "firstTopic" VARCHAR(255),
+                "secondTopic" VARCHAR(255), "thirdTopic" VARCHAR(255), "fourthTopic" VARCHAR(255),
+                "address" VARCHAR(42), "transactionHash" VARCHAR(66), "blockHash" VARCHAR(66),
+                "blockNumber" BIGINT
+            ) ON COMMIT DROP;
+            "#,
+        )
+        .await?;
+
+    let sink = transaction
+        .copy_in(
+            r#"COPY temp_logs ("data", "index", "type", "firstTopic", "secondTopic",
+                "thirdTopic", "fourthTopic", "address", "transactionHash", "blockHash",
+                "blockNumber") FROM STDIN BINARY"#,
+        )
+        .await?;
+    let writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::BYTEA,
+            Type::INT4,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::INT8,
+        ],
     );
-    let contract_type = indexer_types::ContractType::detect_contract_type(abi.clone());
-
-    match contract_type {
-        indexer_types::ContractType::ERC20 => {
-            // Compute the hash of the "Transfer" event signature.
-            let transfer_signature_hash =
-                H256::from(keccak256("Transfer(address,address,uint256)".as_bytes()));
-            debug!("Transfer signature hash: {}", transfer_signature_hash);
-            // Check if the log is a Transfer event
-            if let Some(topic) = log.clone().topics.get(0) {
-                if *topic == transfer_signature_hash {
-                    debug!("Found Transfer {} at block: {}", address, block_number);
-
-                    // Decode the log data
-                    let decoded_log: indexer_types::Transfer = match contract.decode_event(
-                        "Transfer",
-                        log.clone().topics,
-                        log.clone().data,
-                    ) {
-                        Ok(decoded_log) => decoded_log,
-                        Err(e) => {
-                            log_error!("Error decoding log: {}", e);
-                            return Err(Box::new(e));
-                        }
-                    };
-                    debug!("Decoded log: {:?}", decoded_log);
-
-                    // Store the transfer in the database
-                    match tokens::insert_erc20_transfer(
-                        log.clone(),
-                        decoded_log.clone(),
-                        db_pool.clone(),
-                    )
-                    .await
-                    {
-                        Ok(_) => {
-                            debug!("Transfer inserted successfully");
-                            return Ok(());
-                        }
-                        Err(e) => {
-                            log_error!("Error inserting Transfer: {}", e);
-                            return Err(e);
-                        }
-                    }
-                }
-            }
-        }
-        indexer_types::ContractType::Unknown => {
-            debug!("Contract type is unknown");
-        }
-        _ => {
-            //TODO: Handle other contract types
-            warn!("Contract type '{}' is not supported yet", contract_type.to_string());
-        }
+    pin_mut!(writer);
+
+    for log in &logs {
+        let data = log.data.to_vec();
+        let index = log.log_index.unwrap().as_u64() as i32;
+        let log_type = log.log_type.clone();
+        let first_topic = log.topics.get(0).map(|topic| format!("0x{:x}", topic));
+        let second_topic = log.topics.get(1).map(|topic| format!("0x{:x}", topic));
+        let third_topic = log.topics.get(2).map(|topic| format!("0x{:x}", topic));
+        let fourth_topic = log.topics.get(3).map(|topic| format!("0x{:x}", topic));
+        let address = format!("0x{:x}", log.address);
+        let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+        let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+        let block_number = log.block_number.unwrap().as_u64() as i64;
+
+        writer
+            .as_mut()
+            .write(&[
+                &data,
+                &index,
+                &log_type,
+                &first_topic,
+                &second_topic,
+                &third_topic,
+                &fourth_topic,
+                &address,
+                &transaction_hash,
+                &block_hash,
+                &block_number,
+            ])
+            .await?;
     }
+    writer.finish().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            INSERT INTO logs ("data", "index", "type", "firstTopic", "secondTopic",
+                "thirdTopic", "fourthTopic", "address", "transactionHash", "blockHash",
+                "blockNumber", "insertedAt")
+            SELECT *, NOW() FROM temp_logs
+            ON CONFLICT ("transactionHash", "blockHash", "index") DO NOTHING;
+            "#,
+        )
+        .await?;
+
+    transaction.commit().await?;
+    debug!("Bulk-inserted {} logs", logs.len());
+
+    if let Err(e) = decoded_logs::insert_decoded_logs_bulk(&logs, db_pool).await {
+        log_error!("Error bulk-inserting decoded logs: {}", e);
+    }
+
     Ok(())
 }
@@ -2,11 +2,13 @@
 // db/mod.rs
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
+use ethers::types::H256;
+use ethers::utils::keccak256;
 use log::{error as log_error, info};
 use std::env;
 use std::error::Error;
 use std::fs;
-use tokio_postgres::{Client as PostgresClient, NoTls};
+use tokio_postgres::Client as PostgresClient;
 
 mod blocks;
 pub use blocks::*;
@@ -26,8 +28,41 @@ pub use tokens::*;
 mod logs;
 pub use logs::*;
 
+mod decoded_logs;
+
+mod token_transfers;
+pub use token_transfers::index_transfers;
+
+mod token_approvals;
+pub use token_approvals::index_approvals;
+
+mod tls;
+pub use tls::DbTlsConnector;
+
+mod numeric;
+pub use numeric::{SignedU256Numeric, U256Numeric};
+
+mod query;
+pub use query::*;
+
+mod state;
+pub use state::*;
+
+mod failed_blocks;
+pub use failed_blocks::*;
+
+/// Configured ceiling for `connect_db`'s pool (env `DB_POOL_MAX_SIZE`, default 10, bb8's own
+/// default). Exposed separately so `metrics::track_db_pool` can report it without `bb8::Pool`
+/// handing its own builder config back out.
+pub fn configured_pool_max_size() -> u32 {
+    env::var("DB_POOL_MAX_SIZE")
+        .unwrap_or_else(|_| "10".to_string())
+        .parse()
+        .unwrap_or(10)
+}
+
 /// Function to connect to the postgress database
-pub async fn connect_db() -> Pool<PostgresConnectionManager<NoTls>> {
+pub async fn connect_db() -> Pool<PostgresConnectionManager<DbTlsConnector>> {
     let database = env::var("POSTGRES_DB").unwrap();
     let host = env::var("POSTGRES_HOST").unwrap();
     let user = env::var("POSTGRES_USER").unwrap();
@@ -38,20 +73,33 @@ pub async fn connect_db() -> Pool<PostgresConnectionManager<NoTls>> {
         host, port, user, password
     );
     let url_with_db: String = format!("{} dbname={}", url, database);
+
+    let tls_connector = tls::build_tls_connector().expect("Failed to build TLS connector");
+
     // Check if the database exists
-    let database_exists = check_database_exists(&url, &database).await;
+    let database_exists = check_database_exists(&url, &database, tls_connector.clone()).await;
 
     if !database_exists {
         // If the database does not exist, create it
-        create_database(&host, &port, &user, &password, &database, &url)
-            .await
-            .expect("Failed to create database");
+        create_database(
+            &host,
+            &port,
+            &user,
+            &password,
+            &database,
+            &url,
+            tls_connector.clone(),
+        )
+        .await
+        .expect("Failed to create database");
     }
 
-    let manager = PostgresConnectionManager::new_from_stringlike(url_with_db, NoTls)
-        .expect("Failed to create connection manager");
+    let manager =
+        PostgresConnectionManager::new_from_stringlike(url_with_db, tls_connector)
+            .expect("Failed to create connection manager");
 
     let pool = Pool::builder()
+        .max_size(configured_pool_max_size())
         .build(manager)
         .await
         .expect("Failed to create connection pool");
@@ -60,8 +108,12 @@ pub async fn connect_db() -> Pool<PostgresConnectionManager<NoTls>> {
     pool
 }
 
-async fn check_database_exists(url: &str, database_name: &str) -> bool {
-    let (client, connection) = tokio_postgres::connect(url, NoTls)
+async fn check_database_exists(
+    url: &str,
+    database_name: &str,
+    tls_connector: DbTlsConnector,
+) -> bool {
+    let (client, connection) = tokio_postgres::connect(url, tls_connector)
         .await
         .expect("Failed to connect to the database for checking existence");
 
@@ -90,6 +142,7 @@ async fn create_database(
     password: &str,
     database: &str,
     url: &str,
+    tls_connector: DbTlsConnector,
 ) -> Result<PostgresClient, tokio_postgres::Error> {
     info!(
         "Database \"{}\" does not exist. Creating database...",
@@ -101,7 +154,7 @@ async fn create_database(
         "host={} port={} user={} password={}",
         host, port, user, password
     );
-    let (client, connection) = tokio_postgres::connect(&default_url, NoTls).await?;
+    let (client, connection) = tokio_postgres::connect(&default_url, tls_connector.clone()).await?;
 
     tokio::spawn(async move {
         if let Err(e) = connection.await {
@@ -115,7 +168,7 @@ async fn create_database(
         .await?;
 
     // Connect to the newly created database
-    let (client, connection) = tokio_postgres::connect(url, NoTls).await?;
+    let (client, connection) = tokio_postgres::connect(url, tls_connector).await?;
 
     tokio::spawn(async move {
         if let Err(e) = connection.await {
@@ -126,107 +179,145 @@ async fn create_database(
     Ok(client)
 }
 
+/// Directory scanned for numbered SQL migration files (`0001_description.sql`, ...).
+const MIGRATIONS_DIR: &str = "./model";
+
+/// A single migration file discovered under `MIGRATIONS_DIR`.
+struct Migration {
+    version: String,
+    file_name: String,
+    sql: String,
+    checksum: String,
+}
+
 /// Function to initialize the database
 ///
-/// It will check if the configuration table exists and if the version matches
-/// the environment variable. If not, it will execute the SQL files in the
-/// order specified by the environment variable POSTGRES_CREATE_TABLE_ORDER.
-/// It will also update the version in the configuration table.
-///
-/// If the configuration table does not exist, it will execute the SQL files
-/// in the order specified by the environment variable POSTGRES_CREATE_TABLE_ORDER
-/// and create the configuration table with the version specified by the
-/// environment variable VERSION.
-///
-/// If the configuration table exists but the version does not match, it will
-/// execute the SQL files in the order specified by the environment variable
-/// POSTGRES_CREATE_TABLE_ORDER and update the version in the configuration
-/// table with the version specified by the environment variable VERSION.
-///
+/// Tracks individually-applied migrations in a `schema_migrations` table instead of comparing a
+/// single `VERSION` string and re-running every SQL file on mismatch. Every file under
+/// `MIGRATIONS_DIR` named `<version>_<description>.sql` is a migration; versions not yet recorded
+/// in `schema_migrations` are applied in ascending order, each inside its own transaction. A
+/// version that is already recorded but whose file content has since changed fails loudly rather
+/// than silently reapplying or ignoring the drift.
 pub async fn init_db(
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), Box<dyn Error>> {
     let db_client = db_pool.get().await?;
-    let config_version = env::var("VERSION").unwrap_or_default();
-    let config_name = "version";
-
-    // Check if the configuration table exists
-    let table_exists = check_table_exists(&db_client, "configuration").await;
-
-    if table_exists {
-        // Check if the version in the configuration matches the environment variable
-        let version_query = format!(
-            "SELECT config_value FROM configuration WHERE config_name = '{}'",
-            config_name
-        );
 
-        if let Ok(row) = db_client.query_one(&version_query, &[]).await {
-            let stored_version: &str = row.try_get("config_value").unwrap_or_default();
-
-            if stored_version == config_version {
-                // println!("Database is up-to-date. Skipping initialization.");
-                return Ok(());
+    db_client
+        .batch_execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                "version" VARCHAR(32) NOT NULL PRIMARY KEY,
+                "fileName" TEXT NOT NULL,
+                "checksum" VARCHAR(66) NOT NULL,
+                "appliedAt" timestamp default current_timestamp
+            );
+            "#,
+        )
+        .await?;
+    state::ensure_state_table(db_pool.clone()).await?;
+    failed_blocks::ensure_failed_blocks_table(db_pool.clone()).await?;
+
+    let migrations = discover_migrations()?;
+    for migration in migrations {
+        let applied_checksum = db_client
+            .query_opt(
+                r#"SELECT "checksum" FROM schema_migrations WHERE "version" = $1"#,
+                &[&migration.version],
+            )
+            .await?
+            .map(|row| row.get::<_, String>("checksum"));
+
+        match applied_checksum {
+            Some(applied_checksum) if applied_checksum == migration.checksum => {
+                // Already applied and unchanged since then; nothing to do.
+            }
+            Some(applied_checksum) => {
+                return Err(format!(
+                    "Migration {} ({}) has changed since it was applied: checksum {} does not match recorded {}",
+                    migration.version, migration.file_name, migration.checksum, applied_checksum
+                )
+                .into());
+            }
+            None => {
+                info!(
+                    "Applying migration {} ({})",
+                    migration.version, migration.file_name
+                );
+                apply_migration(&db_pool, &migration).await?;
             }
         }
     }
 
-    // If the table doesn't exist or the versions don't match, perform initialization
-    // TODO: perform an update instead on just applying the SQL files
-    let sql_files = env::var("POSTGRES_CREATE_TABLE_ORDER").unwrap();
-    let sql_file_paths: Vec<&str> = sql_files.split(",").collect();
-    for sql_file_path in sql_file_paths {
-        let full_sql_file_path = format!("./model/{}.sql", sql_file_path);
-        info!("executing sql file: {}", full_sql_file_path);
-        let sql = match fs::read_to_string(&full_sql_file_path) {
-            Ok(sql) => sql,
-            Err(e) => panic!("Error reading sql file: {}", e),
-        };
+    Ok(())
+}
+
+/// Scan `MIGRATIONS_DIR` for `<version>_<description>.sql` files and return them sorted by
+/// numeric version.
+fn discover_migrations() -> Result<Vec<Migration>, Box<dyn Error>> {
+    let mut migrations = Vec::new();
 
-        // Execute SQL queries using prepared statements
-        if let Err(e) = db_client.batch_execute(&sql).await {
-            log_error!("Error executing SQL from {}: {}", sql_file_path, e);
-        } else {
-            info!("Executed SQL from: {:?}", sql_file_path);
+    for entry in fs::read_dir(MIGRATIONS_DIR)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !file_name.ends_with(".sql") {
+            continue;
         }
-    }
 
-    // Update the version configuration
-    let update_version_query = format!(
-        "INSERT INTO configuration (config_name, config_value) VALUES ('{}', '{}')
-         ON CONFLICT (config_name) DO UPDATE SET config_value = EXCLUDED.config_value",
-        config_name, config_version
-    );
+        let version = match file_name.split('_').next() {
+            Some(version) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit()) => {
+                version.to_string()
+            }
+            _ => {
+                log_error!(
+                    "Skipping migration file with unexpected name (expected <version>_<description>.sql): {}",
+                    file_name
+                );
+                continue;
+            }
+        };
+
+        let sql = fs::read_to_string(entry.path())?;
+        let checksum = format!("0x{:x}", H256::from(keccak256(sql.as_bytes())));
 
-    if let Err(e) = db_client.batch_execute(&update_version_query).await {
-        log_error!("Error updating version in configuration: {}", e);
+        migrations.push(Migration {
+            version,
+            file_name,
+            sql,
+            checksum,
+        });
     }
 
-    Ok(())
+    migrations.sort_by_key(|migration| migration.version.parse::<u64>().unwrap_or(0));
+    Ok(migrations)
 }
 
-/// Helper function to check if a table exists
-async fn check_table_exists(client: &PostgresClient, table_name: &str) -> bool {
-    let query = format!(
-        "SELECT EXISTS (
-            SELECT 1
-            FROM information_schema.tables
-            WHERE table_name = '{}'
-        )",
-        table_name
-    );
+/// Apply a single migration inside its own transaction, then record its version and checksum so
+/// it is not re-applied on the next run.
+async fn apply_migration(
+    db_pool: &Pool<PostgresConnectionManager<DbTlsConnector>>,
+    migration: &Migration,
+) -> Result<(), Box<dyn Error>> {
+    let mut db_client = db_pool.get().await?;
+    let transaction = db_client.transaction().await?;
+
+    transaction.batch_execute(&migration.sql).await?;
+    transaction
+        .execute(
+            r#"INSERT INTO schema_migrations ("version", "fileName", "checksum") VALUES ($1, $2, $3)"#,
+            &[&migration.version, &migration.file_name, &migration.checksum],
+        )
+        .await?;
 
-    if let Ok(row) = client.query_one(&query, &[]).await {
-        let exists: bool = row.try_get(0).unwrap_or(false);
-        exists
-    } else {
-        false
-    }
+    transaction.commit().await?;
+    info!("Applied migration {} ({})", migration.version, migration.file_name);
+    Ok(())
 }
 
 
 async fn get_abi_by_address(
     address: String,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<serde_json::Value, Box<dyn Error>> {
     let db_client = db_pool.get().await?;
     let query = format!(
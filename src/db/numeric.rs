@@ -0,0 +1,217 @@
+// Module: db::numeric
+//
+// `rust_decimal::Decimal` only has a 96-bit mantissa, so it can represent at most ~28-29
+// significant digits. On-chain wei amounts are `U256` and routinely exceed that (an 18-decimal
+// token amount of more than ~79 billion units is already past it, and a full `U256` runs to 78
+// digits), so converting through `Decimal` silently truncates. This wraps `U256` in a `ToSql` impl that
+// writes the Postgres `NUMERIC` wire format directly from the 256-bit integer, preserving every
+// digit for the `NUMERIC(100)` columns that store it.
+
+use ethers::types::U256;
+use std::error::Error;
+use tokio_postgres::types::{to_sql_checked, IsNull, ToSql, Type};
+
+/// NUMERIC_POS/NUMERIC_NEG sign words from the Postgres wire protocol.
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+
+/// Split `value`'s decimal string into the base-10000 digit groups (most significant first) and
+/// `weight` the NUMERIC wire format wants, dropping leading zero groups. Shared by
+/// [`U256Numeric`] and [`SignedU256Numeric`], which only differ in the sign word they write.
+fn digit_groups(value: U256) -> (i16, Vec<i16>) {
+    let decimal_str = value.to_string();
+    let pad = (4 - decimal_str.len() % 4) % 4;
+    let padded: String = std::iter::repeat('0')
+        .take(pad)
+        .chain(decimal_str.chars())
+        .collect();
+
+    let mut groups: Vec<i16> = padded
+        .as_bytes()
+        .chunks(4)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap().parse().unwrap())
+        .collect();
+
+    // Leading zero groups must be dropped: `weight` names the position of the first digit,
+    // so a leading zero group would misstate where the value actually starts.
+    let first_nonzero = groups.iter().position(|&group| group != 0);
+    let weight = match first_nonzero {
+        Some(idx) => (groups.len() - 1 - idx) as i16,
+        None => {
+            groups.clear();
+            0
+        }
+    };
+    if let Some(idx) = first_nonzero {
+        groups.drain(..idx);
+    }
+
+    (weight, groups)
+}
+
+fn write_numeric(sign: u16, value: U256, out: &mut bytes::BytesMut) {
+    // NUMERIC is sent as: ndigits, weight, sign, dscale, then `ndigits` base-10000 digits, most
+    // significant first. We only ever encode integers, so dscale (the number of digits after the
+    // decimal point) is always 0.
+    let (weight, groups) = digit_groups(value);
+
+    out.extend_from_slice(&(groups.len() as i16).to_be_bytes());
+    out.extend_from_slice(&weight.to_be_bytes());
+    out.extend_from_slice(&sign.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes()); // dscale
+    for group in groups {
+        out.extend_from_slice(&group.to_be_bytes());
+    }
+}
+
+/// A `U256` bound as an exact-precision, non-negative Postgres `NUMERIC`.
+#[derive(Debug, Clone, Copy)]
+pub struct U256Numeric(pub U256);
+
+impl From<U256> for U256Numeric {
+    fn from(value: U256) -> Self {
+        U256Numeric(value)
+    }
+}
+
+impl ToSql for U256Numeric {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        write_numeric(NUMERIC_POS, self.0, out);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::NUMERIC)
+    }
+
+    to_sql_checked!();
+}
+
+/// A signed counterpart to [`U256Numeric`] for columns that need to hold a balance *delta*
+/// (credit or debit) rather than an absolute amount — `rust_decimal::Decimal` is what the rest of
+/// the codebase uses for signed values, but its ~96-bit mantissa overflows for the highest-supply
+/// ERC-20s (a raw `value` on the order of `U256::MAX` is a ~78-digit number). This carries the
+/// full 256-bit magnitude and a separate sign bit instead, so a balance delta can never overflow
+/// or silently truncate regardless of how the token scales its supply.
+#[derive(Debug, Clone, Copy)]
+pub struct SignedU256Numeric {
+    magnitude: U256,
+    negative: bool,
+}
+
+impl SignedU256Numeric {
+    pub fn positive(magnitude: U256) -> Self {
+        SignedU256Numeric { magnitude, negative: false }
+    }
+
+    pub fn negative(magnitude: U256) -> Self {
+        SignedU256Numeric { magnitude, negative: true }
+    }
+}
+
+impl ToSql for SignedU256Numeric {
+    fn to_sql(
+        &self,
+        _ty: &Type,
+        out: &mut bytes::BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        // A magnitude of zero is always encoded as NUMERIC_POS regardless of `negative` — NUMERIC
+        // has no "negative zero", and Postgres rejects NUMERIC_NEG with no digit groups.
+        let sign = if self.negative && !self.magnitude.is_zero() {
+            NUMERIC_NEG
+        } else {
+            NUMERIC_POS
+        };
+        write_numeric(sign, self.magnitude, out);
+        Ok(IsNull::No)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        matches!(*ty, Type::NUMERIC)
+    }
+
+    to_sql_checked!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// NUMERIC's wire format groups digits by 10000 (base-10000), most significant first, with a
+    /// `weight` naming which base-10000 "place" the first group sits at. This value is bigger
+    /// than both `i64::MAX` and `u64::MAX`, which is exactly the case the old
+    /// `Decimal::from(x.as_u128() as i64)` pattern silently truncated.
+    #[test]
+    fn encodes_a_value_larger_than_u64_max() {
+        let value = U256::from(u64::MAX) + U256::from(1); // 2^64 = 18446744073709551616
+        let numeric = U256Numeric::from(value);
+
+        let mut out = bytes::BytesMut::new();
+        let is_null = numeric.to_sql(&Type::NUMERIC, &mut out).unwrap();
+        assert_eq!(is_null, IsNull::No);
+
+        let mut expected = bytes::BytesMut::new();
+        expected.extend_from_slice(&5i16.to_be_bytes()); // ndigits: 1844,6744,0737,0955,1616
+        expected.extend_from_slice(&4i16.to_be_bytes()); // weight
+        expected.extend_from_slice(&0u16.to_be_bytes()); // sign: NUMERIC_POS
+        expected.extend_from_slice(&0u16.to_be_bytes()); // dscale
+        for group in [1844i16, 6744, 737, 955, 1616] {
+            expected.extend_from_slice(&group.to_be_bytes());
+        }
+
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn encodes_zero_with_no_digit_groups() {
+        let numeric = U256Numeric::from(U256::zero());
+
+        let mut out = bytes::BytesMut::new();
+        numeric.to_sql(&Type::NUMERIC, &mut out).unwrap();
+
+        let mut expected = bytes::BytesMut::new();
+        expected.extend_from_slice(&0i16.to_be_bytes()); // ndigits
+        expected.extend_from_slice(&0i16.to_be_bytes()); // weight
+        expected.extend_from_slice(&0u16.to_be_bytes()); // sign: NUMERIC_POS
+        expected.extend_from_slice(&0u16.to_be_bytes()); // dscale
+
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn signed_negative_sets_the_numeric_neg_sign_word() {
+        let numeric = SignedU256Numeric::negative(U256::from(1616u64));
+
+        let mut out = bytes::BytesMut::new();
+        numeric.to_sql(&Type::NUMERIC, &mut out).unwrap();
+
+        let mut expected = bytes::BytesMut::new();
+        expected.extend_from_slice(&1i16.to_be_bytes()); // ndigits
+        expected.extend_from_slice(&0i16.to_be_bytes()); // weight
+        expected.extend_from_slice(&0x4000u16.to_be_bytes()); // sign: NUMERIC_NEG
+        expected.extend_from_slice(&0u16.to_be_bytes()); // dscale
+        expected.extend_from_slice(&1616i16.to_be_bytes());
+
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn signed_zero_is_never_negative() {
+        let numeric = SignedU256Numeric::negative(U256::zero());
+
+        let mut out = bytes::BytesMut::new();
+        numeric.to_sql(&Type::NUMERIC, &mut out).unwrap();
+
+        let mut expected = bytes::BytesMut::new();
+        expected.extend_from_slice(&0i16.to_be_bytes()); // ndigits
+        expected.extend_from_slice(&0i16.to_be_bytes()); // weight
+        expected.extend_from_slice(&0u16.to_be_bytes()); // sign: NUMERIC_POS, never NUMERIC_NEG
+        expected.extend_from_slice(&0u16.to_be_bytes()); // dscale
+
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,217 @@
+// Module: db::query
+//
+// Every other module in `db` writes. These are the read-only lookups backing the `index_serve`
+// HTTP API (see `crate::server`), so a consumer can fetch already-indexed data as JSON instead of
+// writing SQL against the database directly. NUMERIC columns are cast to text in the query itself
+// rather than decoded back into `U256Numeric`/`Decimal` in Rust, since `U256Numeric` only
+// implements `ToSql` and `Decimal` can't hold a full `U256`; the rest of the row comes back through `tokio_postgres`'s normal `FromSql`
+// impls.
+use crate::db::DbTlsConnector;
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use serde_json::{json, Value};
+use std::error::Error;
+
+/// Fetch a single transaction by its hash, or `Value::Null` if it isn't indexed.
+pub async fn get_transaction_by_hash(
+    hash: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Value, Box<dyn Error>> {
+    let db_client = db_pool.get().await?;
+    let row = db_client
+        .query_opt(
+            r#"
+            SELECT "hash", "from", "to", "blockHash", "blockNumber", "transactionIndex",
+                   "nonce", "gas", "input", "type", "chainId",
+                   "value"::text AS "value", "gasPrice"::text AS "gasPrice",
+                   "maxFeePerGas"::text AS "maxFeePerGas",
+                   "maxPriorityFeePerGas"::text AS "maxPriorityFeePerGas",
+                   "maxFeePerBlobGas"::text AS "maxFeePerBlobGas",
+                   "accessList", "blobVersionedHashes"
+            FROM transactions
+            WHERE "hash" = $1
+            "#,
+            &[&hash],
+        )
+        .await?;
+
+    let row = match row {
+        Some(row) => row,
+        None => return Ok(Value::Null),
+    };
+
+    Ok(json!({
+        "hash": row.get::<_, String>("hash"),
+        "from": row.get::<_, String>("from"),
+        "to": row.get::<_, Option<String>>("to"),
+        "blockHash": row.get::<_, Option<String>>("blockHash"),
+        "blockNumber": row.get::<_, i64>("blockNumber"),
+        "transactionIndex": row.get::<_, i32>("transactionIndex"),
+        "nonce": row.get::<_, i32>("nonce"),
+        "gas": row.get::<_, i32>("gas"),
+        "input": row.get::<_, String>("input"),
+        "type": row.get::<_, i16>("type"),
+        "chainId": row.get::<_, Option<String>>("chainId"),
+        "value": row.get::<_, Option<String>>("value"),
+        "gasPrice": row.get::<_, Option<String>>("gasPrice"),
+        "maxFeePerGas": row.get::<_, Option<String>>("maxFeePerGas"),
+        "maxPriorityFeePerGas": row.get::<_, Option<String>>("maxPriorityFeePerGas"),
+        "maxFeePerBlobGas": row.get::<_, Option<String>>("maxFeePerBlobGas"),
+        "accessList": row.get::<_, Option<Value>>("accessList"),
+        "blobVersionedHashes": row.get::<_, Option<Value>>("blobVersionedHashes"),
+    }))
+}
+
+/// Fetch a transaction's receipt by the transaction's hash, or `Value::Null` if neither the
+/// transaction nor its receipt is indexed yet. Joins `transactions` and `transactions_receipts`
+/// on `"hash"`/`"transactionHash"` so the response carries both the transaction's envelope fields
+/// and the receipt's execution outcome in one lookup.
+pub async fn get_transaction_receipt_by_hash(
+    hash: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Value, Box<dyn Error>> {
+    let db_client = db_pool.get().await?;
+    let row = db_client
+        .query_opt(
+            r#"
+            SELECT t."hash" AS "transactionHash", t."type" AS "transactionType",
+                   r."transactionIndex", r."blockHash", r."blockNumber", r."from", r."to",
+                   r."contractAddress", r."status", r."logs", r."logsBloom",
+                   r."cumulativeGasUsed"::text AS "cumulativeGasUsed",
+                   r."gasUsed"::text AS "gasUsed",
+                   r."effectiveGasPrice"::text AS "effectiveGasPrice",
+                   r."blobGasUsed"::text AS "blobGasUsed",
+                   r."blobGasPrice"::text AS "blobGasPrice"
+            FROM transactions t
+            JOIN transactions_receipts r ON r."transactionHash" = t."hash"
+            WHERE t."hash" = $1
+            "#,
+            &[&hash],
+        )
+        .await?;
+
+    let row = match row {
+        Some(row) => row,
+        None => return Ok(Value::Null),
+    };
+
+    Ok(json!({
+        "transactionHash": row.get::<_, String>("transactionHash"),
+        "transactionType": row.get::<_, i16>("transactionType"),
+        "transactionIndex": row.get::<_, i32>("transactionIndex"),
+        "blockHash": row.get::<_, String>("blockHash"),
+        "blockNumber": row.get::<_, i64>("blockNumber"),
+        "from": row.get::<_, String>("from"),
+        "to": row.get::<_, Option<String>>("to"),
+        "contractAddress": row.get::<_, Option<String>>("contractAddress"),
+        "status": row.get::<_, bool>("status"),
+        "logs": row.get::<_, Option<Value>>("logs"),
+        "logsBloom": row.get::<_, String>("logsBloom"),
+        "cumulativeGasUsed": row.get::<_, Option<String>>("cumulativeGasUsed"),
+        "gasUsed": row.get::<_, Option<String>>("gasUsed"),
+        "effectiveGasPrice": row.get::<_, Option<String>>("effectiveGasPrice"),
+        "blobGasUsed": row.get::<_, Option<String>>("blobGasUsed"),
+        "blobGasPrice": row.get::<_, Option<String>>("blobGasPrice"),
+    }))
+}
+
+/// List the transactions belonging to a block, identified either by its number (a bare decimal
+/// string) or its hash (a `0x`-prefixed string). Returns an empty array, not `Value::Null`, when
+/// the block isn't indexed or has no transactions.
+pub async fn get_block_transactions(
+    number_or_hash: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Value, Box<dyn Error>> {
+    let db_client = db_pool.get().await?;
+
+    let rows = if let Ok(block_number) = number_or_hash.parse::<i64>() {
+        db_client
+            .query(
+                r#"
+                SELECT "hash", "from", "to", "transactionIndex", "nonce",
+                       "value"::text AS "value"
+                FROM transactions
+                WHERE "blockNumber" = $1
+                ORDER BY "transactionIndex" ASC
+                "#,
+                &[&block_number],
+            )
+            .await?
+    } else {
+        db_client
+            .query(
+                r#"
+                SELECT "hash", "from", "to", "transactionIndex", "nonce",
+                       "value"::text AS "value"
+                FROM transactions
+                WHERE "blockHash" = $1
+                ORDER BY "transactionIndex" ASC
+                "#,
+                &[&number_or_hash],
+            )
+            .await?
+    };
+
+    let transactions: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "hash": row.get::<_, String>("hash"),
+                "from": row.get::<_, String>("from"),
+                "to": row.get::<_, Option<String>>("to"),
+                "transactionIndex": row.get::<_, i32>("transactionIndex"),
+                "nonce": row.get::<_, i32>("nonce"),
+                "value": row.get::<_, Option<String>>("value"),
+            })
+        })
+        .collect();
+
+    Ok(Value::Array(transactions))
+}
+
+/// Number of token transfers returned per `get_token_transfers_for_address` call. There is no
+/// pagination yet, so this just bounds the response to the most recent transfers rather than
+/// returning an address's entire history in one request.
+const TOKEN_TRANSFERS_LIMIT: i64 = 100;
+
+/// List the most recent token transfers (ERC-20, ERC-721 or ERC-1155, see
+/// `token_transfers::index_transfers`)
+/// where `address` is either the sender or the recipient, newest first.
+pub async fn get_token_transfers_for_address(
+    address: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Value, Box<dyn Error>> {
+    let db_client = db_pool.get().await?;
+    let rows = db_client
+        .query(
+            r#"
+            SELECT "contractAddress", "fromAddress", "toAddress", "transactionHash",
+                   "blockNumber", "blockHash", "logIndex", "amount"::text AS "amount", "standard"
+            FROM token_transfers
+            WHERE "fromAddress" = $1 OR "toAddress" = $1
+            ORDER BY "blockNumber" DESC, "logIndex" DESC
+            LIMIT $2
+            "#,
+            &[&address, &TOKEN_TRANSFERS_LIMIT],
+        )
+        .await?;
+
+    let transfers: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "contractAddress": row.get::<_, String>("contractAddress"),
+                "fromAddress": row.get::<_, Option<String>>("fromAddress"),
+                "toAddress": row.get::<_, Option<String>>("toAddress"),
+                "transactionHash": row.get::<_, String>("transactionHash"),
+                "blockNumber": row.get::<_, i64>("blockNumber"),
+                "blockHash": row.get::<_, Option<String>>("blockHash"),
+                "logIndex": row.get::<_, i32>("logIndex"),
+                "amount": row.get::<_, Option<String>>("amount"),
+                "standard": row.get::<_, Option<String>>("standard"),
+            })
+        })
+        .collect();
+
+    Ok(Value::Array(transfers))
+}
@@ -0,0 +1,88 @@
+// Module: db::state
+//
+// Tracks indexing progress across restarts so a crashed or redeployed container can resume
+// instead of either re-indexing everything from `START_BLOCK` or requiring manual bookkeeping.
+//
+// Database schema:
+// CREATE TABLE indexer_state (
+//     "id" SMALLINT PRIMARY KEY DEFAULT 1 CHECK ("id" = 1),
+//     "highestContiguousBlock" BIGINT NOT NULL,
+//     "updatedAt" timestamp default current_timestamp
+// );
+//
+// The `CHECK ("id" = 1)` pins the table to a single row: there is only ever one cursor, so
+// `set_cursor` always upserts that one row rather than appending a history of them.
+use crate::db::DbTlsConnector;
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use ethers::prelude::*;
+use log::error as log_error;
+use std::error::Error;
+
+/// Ensure the `indexer_state` table exists. Called from `init_db` alongside `schema_migrations`,
+/// since this is bootstrap state the indexer itself owns rather than a per-entity table shipped
+/// via a `model/` migration.
+pub async fn ensure_state_table(
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    db_client
+        .batch_execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS indexer_state (
+                "id" SMALLINT PRIMARY KEY DEFAULT 1 CHECK ("id" = 1),
+                "highestContiguousBlock" BIGINT NOT NULL,
+                "updatedAt" timestamp default current_timestamp
+            );
+            "#,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Read the highest contiguously-indexed block persisted by `set_cursor`, or `None` if nothing
+/// has been indexed yet (fresh database, or one that predates this table).
+pub async fn get_cursor(
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<Option<U64>, Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let row = db_client
+        .query_opt(
+            r#"SELECT "highestContiguousBlock" FROM indexer_state WHERE "id" = 1"#,
+            &[],
+        )
+        .await?;
+    Ok(row.map(|row| U64::from(row.get::<_, i64>("highestContiguousBlock") as u64)))
+}
+
+/// Persist `block` as the new highest contiguously-indexed block. Called once a run of
+/// `index_blocks` finishes its range successfully, so the next `Indexer::run` resumes right
+/// after it instead of redoing work from `START_BLOCK`.
+pub async fn set_cursor(
+    block: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    db_client
+        .execute(
+            r#"
+            INSERT INTO indexer_state ("id", "highestContiguousBlock", "updatedAt")
+            VALUES (1, $1, NOW())
+            ON CONFLICT ("id") DO UPDATE SET
+            "highestContiguousBlock" = EXCLUDED."highestContiguousBlock",
+            "updatedAt" = NOW()
+            "#,
+            &[&(block.as_u64() as i64)],
+        )
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,207 @@
+// Module: db::tls
+//
+// `connect_db` used to hardcode `NoTls`, which can't talk to managed Postgres instances that
+// require encrypted connections. `DbTlsConnector`/`DbTlsStream` are a small enum pair that
+// implement the `tokio_postgres` TLS traits by delegating to either a plain passthrough or a
+// `postgres-native-tls` connector, so `PostgresConnectionManager` and every `bb8::Pool` built
+// from it can stay a single concrete type regardless of which mode is active at runtime.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::warn;
+use native_tls::{Certificate, Identity, TlsConnector as NativeTlsConnector};
+use postgres_native_tls::{MakeTlsConnector, TlsStream as NativeTlsStream};
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_postgres::tls::{ChannelBinding, MakeTlsConnect, NoTls, TlsConnect};
+use tokio_postgres::Socket;
+
+/// Connector used for every pool built by `db::connect_db`. Either mode (`Disabled`/`Enabled`)
+/// is picked once, at startup, from the configured `SslMode`.
+#[derive(Clone)]
+pub enum DbTlsConnector {
+    Disabled(NoTls),
+    Enabled(MakeTlsConnector<NativeTlsConnector>),
+}
+
+/// The stream type produced by `DbTlsConnector`: either the raw socket, or a native-tls stream
+/// wrapping it.
+pub enum DbTlsStream {
+    Plain(Socket),
+    Tls(NativeTlsStream<Socket>),
+}
+
+impl AsyncRead for DbTlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            DbTlsStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            DbTlsStream::Tls(s) => Pin::new(s).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for DbTlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            DbTlsStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            DbTlsStream::Tls(s) => Pin::new(s).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            DbTlsStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            DbTlsStream::Tls(s) => Pin::new(s).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            DbTlsStream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            DbTlsStream::Tls(s) => Pin::new(s).poll_shutdown(cx),
+        }
+    }
+}
+
+impl tokio_postgres::tls::TlsStream for DbTlsStream {
+    fn channel_binding(&self) -> ChannelBinding {
+        match self {
+            DbTlsStream::Plain(_) => ChannelBinding::none(),
+            DbTlsStream::Tls(s) => s.channel_binding(),
+        }
+    }
+}
+
+pub enum DbTlsConnect {
+    Disabled(<NoTls as MakeTlsConnect<Socket>>::TlsConnect),
+    Enabled(<MakeTlsConnector<NativeTlsConnector> as MakeTlsConnect<Socket>>::TlsConnect),
+}
+
+pub enum DbTlsConnectFuture {
+    Disabled(<<NoTls as MakeTlsConnect<Socket>>::TlsConnect as TlsConnect<Socket>>::Future),
+    Enabled(
+        <<MakeTlsConnector<NativeTlsConnector> as MakeTlsConnect<Socket>>::TlsConnect as TlsConnect<
+            Socket,
+        >>::Future,
+    ),
+}
+
+impl Future for DbTlsConnectFuture {
+    type Output = Result<DbTlsStream, std::io::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut() {
+            DbTlsConnectFuture::Disabled(fut) => {
+                Pin::new(fut).poll(cx).map_ok(DbTlsStream::Plain)
+            }
+            DbTlsConnectFuture::Enabled(fut) => Pin::new(fut)
+                .poll(cx)
+                .map_ok(DbTlsStream::Tls)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)),
+        }
+    }
+}
+
+impl TlsConnect<Socket> for DbTlsConnect {
+    type Stream = DbTlsStream;
+    type Error = std::io::Error;
+    type Future = DbTlsConnectFuture;
+
+    fn connect(self, stream: Socket) -> Self::Future {
+        match self {
+            DbTlsConnect::Disabled(connect) => DbTlsConnectFuture::Disabled(connect.connect(stream)),
+            DbTlsConnect::Enabled(connect) => DbTlsConnectFuture::Enabled(connect.connect(stream)),
+        }
+    }
+}
+
+impl MakeTlsConnect<Socket> for DbTlsConnector {
+    type Stream = DbTlsStream;
+    type TlsConnect = DbTlsConnect;
+    type Error = std::io::Error;
+
+    fn make_tls_connect(&mut self, domain: &str) -> Result<Self::TlsConnect, Self::Error> {
+        match self {
+            DbTlsConnector::Disabled(no_tls) => {
+                Ok(DbTlsConnect::Disabled(no_tls.make_tls_connect(domain)?))
+            }
+            DbTlsConnector::Enabled(connector) => Ok(DbTlsConnect::Enabled(
+                connector
+                    .make_tls_connect(domain)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?,
+            )),
+        }
+    }
+}
+
+/// Recognized values for `POSTGRES_SSL_MODE`. `Prefer` and `Require` currently build the
+/// identical TLS connector below (there's no "probe the server, then fall back to plaintext"
+/// negotiation the way `libpq`'s own `prefer` does); the enum exists so an unrecognized value is
+/// reported instead of `eq_ignore_ascii_case("disable")` silently treating any typo as "enable
+/// TLS".
+enum SslMode {
+    Disable,
+    Prefer,
+    Require,
+}
+
+impl SslMode {
+    fn parse(raw: &str) -> SslMode {
+        match raw.to_lowercase().as_str() {
+            "disable" => SslMode::Disable,
+            "prefer" => SslMode::Prefer,
+            "require" => SslMode::Require,
+            other => {
+                warn!(
+                    "Unrecognized POSTGRES_SSL_MODE '{}', defaulting to 'require'",
+                    other
+                );
+                SslMode::Require
+            }
+        }
+    }
+}
+
+/// Build the `DbTlsConnector` to use for every pool, based on the `POSTGRES_SSL_MODE`
+/// environment variable (`disable` (default), `prefer`, `require`). In `prefer`/`require` mode,
+/// an optional CA certificate, client PKCS#12 identity, and identity password are read from
+/// `POSTGRES_SSL_CA`, `POSTGRES_SSL_CLIENT_CERT`, and `POSTGRES_SSL_CLIENT_CERT_PASSWORD`, each
+/// expected to be base64-encoded.
+pub fn build_tls_connector() -> Result<DbTlsConnector, Box<dyn std::error::Error>> {
+    let ssl_mode = SslMode::parse(&env::var("POSTGRES_SSL_MODE").unwrap_or_else(|_| "disable".to_string()));
+
+    if matches!(ssl_mode, SslMode::Disable) {
+        return Ok(DbTlsConnector::Disabled(NoTls));
+    }
+
+    let mut builder = NativeTlsConnector::builder();
+
+    if let Ok(ca_cert_b64) = env::var("POSTGRES_SSL_CA") {
+        let ca_cert_pem = STANDARD.decode(ca_cert_b64)?;
+        let ca_cert = Certificate::from_pem(&ca_cert_pem)?;
+        builder.add_root_certificate(ca_cert);
+    }
+
+    if let Ok(client_cert_b64) = env::var("POSTGRES_SSL_CLIENT_CERT") {
+        let client_cert_pkcs12 = STANDARD.decode(client_cert_b64)?;
+        let identity_password =
+            env::var("POSTGRES_SSL_CLIENT_CERT_PASSWORD").unwrap_or_default();
+        let identity = Identity::from_pkcs12(&client_cert_pkcs12, &identity_password)?;
+        builder.identity(identity);
+    }
+
+    let native_connector = builder.build()?;
+    Ok(DbTlsConnector::Enabled(MakeTlsConnector::new(
+        native_connector,
+    )))
+}
@@ -0,0 +1,103 @@
+// Module: db::token_approvals
+//
+// Mirrors `token_transfers::index_transfers`: scans a transaction receipt's raw logs for
+// `Approval`/`ApprovalForAll` signatures directly, independent of whether the emitting contract
+// has a verified ABI on file, and persists each one into `token_approvals`.
+use crate::db::{tokens, DbTlsConnector};
+use crate::indexer_types;
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use ethers::abi::{AbiDecode, AbiError};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use log::error as log_error;
+use std::error::Error;
+
+/// Scan every log in `receipt` for an `Approval` or `ApprovalForAll` event and persist each one
+/// into `token_approvals`.
+///
+/// `Approval(address indexed owner, address indexed spender, uint256 value)` has the same
+/// ERC-20-vs-ERC-721 topic-count ambiguity `Transfer` does (3 topics with `value` in `data` for
+/// ERC-20, 4 topics with the approved `tokenId` as the last one and empty `data` for ERC-721).
+/// `ApprovalForAll(address indexed owner, address indexed operator, bool approved)` has its own
+/// unambiguous signature and always carries 3 topics with `approved` in `data`.
+pub async fn index_approvals(
+    receipt: &TransactionReceipt,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let approval_signature = H256::from(keccak256("Approval(address,address,uint256)".as_bytes()));
+    let approval_for_all_signature =
+        H256::from(keccak256("ApprovalForAll(address,address,bool)".as_bytes()));
+
+    for log in &receipt.logs {
+        let Some(signature) = log.topics.first() else {
+            continue;
+        };
+
+        if *signature == approval_signature {
+            match log.topics.len() {
+                3 => {
+                    let decoded = match decode_approval(&log.topics[1..], log.data.as_ref()) {
+                        Ok(decoded) => decoded,
+                        Err(e) => {
+                            log_error!("Error decoding ERC20 Approval log: {}", e);
+                            continue;
+                        }
+                    };
+                    tokens::insert_approval(log, decoded, "ERC20", db_pool.clone()).await?;
+                }
+                4 => {
+                    let decoded = match decode_approval(&log.topics[1..], &[]) {
+                        Ok(decoded) => decoded,
+                        Err(e) => {
+                            log_error!("Error decoding ERC721 Approval log: {}", e);
+                            continue;
+                        }
+                    };
+                    tokens::insert_approval(log, decoded, "ERC721", db_pool.clone()).await?;
+                }
+                _ => {}
+            }
+        } else if *signature == approval_for_all_signature {
+            let decoded = match decode_approval_for_all(&log.topics[1..], log.data.as_ref()) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    log_error!("Error decoding ApprovalForAll log: {}", e);
+                    continue;
+                }
+            };
+            tokens::insert_approval_for_all(log, decoded, db_pool.clone()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rebuild the ABI-encoded `(owner, spender, value)` tuple the indexed topics and `data` would
+/// have formed had none of the parameters been indexed, then decode it through
+/// `indexer_types::Approval`. Same reconstruction `token_transfers::decode_transfer` uses, since
+/// `owner`/`spender` are addresses and `value` a uint256 — all static, 32-byte-word types.
+fn decode_approval(
+    indexed_topics: &[H256],
+    data: &[u8],
+) -> Result<indexer_types::Approval, AbiError> {
+    decode_indexed(indexed_topics, data)
+}
+
+/// Same reconstruction as [`decode_approval`], for `indexer_types::ApprovalForAll`'s
+/// `(owner, operator, approved)` tuple.
+fn decode_approval_for_all(
+    indexed_topics: &[H256],
+    data: &[u8],
+) -> Result<indexer_types::ApprovalForAll, AbiError> {
+    decode_indexed(indexed_topics, data)
+}
+
+fn decode_indexed<T: AbiDecode>(indexed_topics: &[H256], data: &[u8]) -> Result<T, AbiError> {
+    let mut encoded = Vec::with_capacity(indexed_topics.len() * 32 + data.len());
+    for topic in indexed_topics {
+        encoded.extend_from_slice(topic.as_bytes());
+    }
+    encoded.extend_from_slice(data);
+    T::decode(&encoded)
+}
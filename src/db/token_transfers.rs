@@ -0,0 +1,344 @@
+// Module: db::token_transfers
+//
+// Token transfers are picked up here rather than from ABI-decoded logs, so a contract needs no
+// verified ABI on file for its transfers to be indexed. The
+// `Transfer(address,address,uint256)` topic hash is the same regardless of whether the ABI is
+// known, so this scans a transaction receipt's raw logs for that signature directly and tells
+// ERC-20 apart from ERC-721 purely by topic count, with no ABI lookup in the loop.
+use crate::db::{contracts, tokens, DbTlsConnector, U256Numeric};
+use crate::indexer_types;
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use ethers::abi::{self, AbiDecode, AbiError, ParamType};
+use ethers::prelude::*;
+use ethers::utils::keccak256;
+use log::{debug, error as log_error};
+use std::error::Error;
+use tokio_postgres::types::ToSql;
+
+/// Scan every log in `receipt` for a `Transfer`, `TransferSingle`, or `TransferBatch` event and
+/// persist each one into `token_transfers`.
+///
+/// ERC-20's `Transfer(address indexed from, address indexed to, uint256 value)` leaves 3 topics
+/// (signature, from, to) with `value` in `data`. ERC-721's
+/// `Transfer(address indexed from, address indexed to, uint256 indexed tokenId)` leaves 4 topics
+/// with `tokenId` as the last one and an empty `data`. Topic count is what tells them apart, with
+/// one wrinkle: a handful of older ERC-20s mark `value` indexed too, so their legacy Transfer has
+/// the exact same 4-topic/empty-data shape as an ERC-721 one. When that shape comes up, this
+/// falls back to the contract's already-detected standard to pick the right table; see the
+/// 4-topic branch below. ERC-1155's `TransferSingle`/`TransferBatch` have their own, unambiguous
+/// signatures.
+pub async fn index_transfers(
+    receipt: &TransactionReceipt,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let transfer_signature = H256::from(keccak256("Transfer(address,address,uint256)".as_bytes()));
+    let transfer_single_signature = H256::from(keccak256(
+        "TransferSingle(address,address,address,uint256,uint256)".as_bytes(),
+    ));
+    let transfer_batch_signature = H256::from(keccak256(
+        "TransferBatch(address,address,address,uint256[],uint256[])".as_bytes(),
+    ));
+
+    for log in &receipt.logs {
+        let Some(signature) = log.topics.first() else {
+            continue;
+        };
+
+        if *signature == transfer_signature {
+            match log.topics.len() {
+                3 => {
+                    let decoded = match decode_transfer(&log.topics[1..], log.data.as_ref()) {
+                        Ok(decoded) => decoded,
+                        Err(e) => {
+                            log_error!("Error decoding ERC20 Transfer log: {}", e);
+                            continue;
+                        }
+                    };
+                    tokens::insert_erc20_transfer(log.clone(), decoded, db_pool.clone()).await?;
+                    crate::metrics::TOKEN_TRANSFERS_INDEXED.inc();
+                }
+                4 => {
+                    let decoded = match decode_transfer(&log.topics[1..], &[]) {
+                        Ok(decoded) => decoded,
+                        Err(e) => {
+                            log_error!("Error decoding ERC721 Transfer log: {}", e);
+                            continue;
+                        }
+                    };
+
+                    // A legacy ERC-20 with a non-conformant `value indexed` Transfer has the
+                    // exact same shape on the wire as a real ERC-721 Transfer: 4 topics, empty
+                    // `data`. The only signal that breaks the tie is the contract's already-
+                    // detected standard, so defer to ERC-721 unless we specifically know this
+                    // address is an ERC-20.
+                    match contracts::get_contract_type(log.address, db_pool.clone()).await {
+                        Ok(Some(contract_type)) if contract_type == "ERC20" => {
+                            tokens::insert_erc20_transfer(log.clone(), decoded, db_pool.clone())
+                                .await?;
+                            crate::metrics::TOKEN_TRANSFERS_INDEXED.inc();
+                        }
+                        Ok(_) => {
+                            insert_erc721_transfer(log.clone(), decoded, db_pool.clone()).await?;
+                            crate::metrics::TOKEN_TRANSFERS_INDEXED.inc();
+                        }
+                        Err(e) => {
+                            log_error!(
+                                "Error looking up contract type for {:?}, assuming ERC721: {}",
+                                log.address,
+                                e
+                            );
+                            insert_erc721_transfer(log.clone(), decoded, db_pool.clone()).await?;
+                            crate::metrics::TOKEN_TRANSFERS_INDEXED.inc();
+                        }
+                    }
+                }
+                topic_count => {
+                    debug!(
+                        "Transfer-signature log with unexpected topic count {} at {:?}",
+                        topic_count, log.transaction_hash
+                    );
+                }
+            }
+        } else if *signature == transfer_single_signature {
+            let decoded: indexer_types::TransferSingle =
+                match decode_indexed(&log.topics[1..], log.data.as_ref()) {
+                    Ok(decoded) => decoded,
+                    Err(e) => {
+                        log_error!("Error decoding ERC1155 TransferSingle log: {}", e);
+                        continue;
+                    }
+                };
+            tokens::insert_erc1155_transfer(
+                log,
+                decoded.operator,
+                decoded.from,
+                decoded.to,
+                decoded.id,
+                decoded.value,
+                0,
+                db_pool.clone(),
+            )
+            .await?;
+            crate::metrics::TOKEN_TRANSFERS_INDEXED.inc();
+        } else if *signature == transfer_batch_signature {
+            if let Err(e) = index_transfer_batch(log, &db_pool).await {
+                log_error!("Error decoding ERC1155 TransferBatch log: {}", e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Rebuild the ABI-encoded tuple the indexed topics and `data` would have formed had none of the
+/// event's parameters been indexed, then decode it through an `AbiDecode` type shaped like that
+/// tuple. Every indexed topic is already a 32-byte word, so the reconstruction is just
+/// concatenation; this only works for events whose indexed parameters are all static types
+/// (addresses, uints), which holds for `Transfer` and `TransferSingle` but not `TransferBatch`'s
+/// dynamic `uint256[]` arrays.
+fn decode_indexed<T: AbiDecode>(indexed_topics: &[H256], data: &[u8]) -> Result<T, AbiError> {
+    let mut encoded = Vec::with_capacity(indexed_topics.len() * 32 + data.len());
+    for topic in indexed_topics {
+        encoded.extend_from_slice(topic.as_bytes());
+    }
+    encoded.extend_from_slice(data);
+    T::decode(&encoded)
+}
+
+fn decode_transfer(
+    indexed_topics: &[H256],
+    data: &[u8],
+) -> Result<indexer_types::Transfer, AbiError> {
+    decode_indexed(indexed_topics, data)
+}
+
+/// Decode a `TransferBatch(address indexed operator, address indexed from, address indexed to,
+/// uint256[] ids, uint256[] values)` log and insert one `token_transfers` row per `(id, value)`
+/// pair, since every other event this module handles maps to exactly one row. `operator`/`from`/
+/// `to` come straight off the indexed topics (each a 32-byte word with the address in its low 20
+/// bytes); `ids`/`values` are dynamic arrays that only exist in `data`, ABI-encoded as a plain
+/// `(uint256[], uint256[])` tuple since none of their elements are indexed.
+async fn index_transfer_batch(
+    log: &Log,
+    db_pool: &Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    if log.topics.len() < 4 {
+        return Err(format!(
+            "TransferBatch-signature log with unexpected topic count {} at {:?}",
+            log.topics.len(),
+            log.transaction_hash
+        )
+        .into());
+    }
+
+    let operator = Address::from(log.topics[1]);
+    let from = Address::from(log.topics[2]);
+    let to = Address::from(log.topics[3]);
+
+    let decoded_tokens = abi::decode(
+        &[
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+        ],
+        log.data.as_ref(),
+    )?;
+    let [ids, values]: [_; 2] = decoded_tokens.try_into().expect("decode() returned 2 params");
+    let ids = ids.into_array().expect("ids is an Array token");
+    let values = values.into_array().expect("values is an Array token");
+
+    if ids.len() != values.len() {
+        return Err(format!(
+            "TransferBatch ids/values length mismatch: {} vs {}",
+            ids.len(),
+            values.len()
+        )
+        .into());
+    }
+
+    for (batch_index, (id, value)) in ids.into_iter().zip(values).enumerate() {
+        let id = id.into_uint().expect("ids element is a Uint token");
+        let value = value.into_uint().expect("values element is a Uint token");
+        tokens::insert_erc1155_transfer(
+            log,
+            operator,
+            from,
+            to,
+            id,
+            value,
+            batch_index as i32,
+            db_pool.clone(),
+        )
+        .await?;
+        crate::metrics::TOKEN_TRANSFERS_INDEXED.inc();
+    }
+
+    Ok(())
+}
+
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Function to insert an ERC721 transfer into the database, and fold its effect into
+/// `nft_token_owners` the same way `tokens::insert_erc20_transfer` folds ERC-20 transfers into
+/// `token_balances`: a fungible balance delta doesn't apply to a 1-of-1 NFT, so this just
+/// repoints `tokenId`'s single owner row instead, deleting it on a burn.
+///
+/// Database schema: see `tokens::insert_erc20_transfer`'s `token_transfers` doc comment for that
+/// table; `tokenId` holds the transferred token, `amount` is left NULL, and `standard` is always
+/// `'ERC721'`.
+/// CREATE TABLE "nft_token_owners" (
+///     "contractAddress" VARCHAR(42) NOT NULL,
+///     "tokenId" NUMERIC(100) NOT NULL,
+///     "ownerAddress" VARCHAR(42) NOT NULL,
+///     "updatedAtBlock" BIGINT NOT NULL,
+///     "lastUpdated" timestamp default current_timestamp,
+///     CONSTRAINT nft_token_owners_pkey PRIMARY KEY ("contractAddress", "tokenId")
+/// );
+async fn insert_erc721_transfer(
+    log: Log,
+    decoded_log: indexer_types::Transfer,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    debug!("Inserting ERC721 transfer: {:?}", log);
+
+    // Extract relevant data from the log
+    let contract_address = format!("0x{:x}", log.address);
+    let from_address = format!("0x{:x}", decoded_log.from);
+    let to_address = format!("0x{:x}", decoded_log.to);
+    let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+    let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+    let block_number = log.block_number.unwrap().as_u64() as i64;
+    let log_index = log.log_index.unwrap().as_u64() as i32;
+    let token_id = U256Numeric::from(decoded_log.value);
+
+    // Build the SQL query
+    let query = r#"
+        INSERT INTO token_transfers
+        ("contractAddress", "fromAddress", "toAddress", "transactionHash", "blockNumber", "blockHash", "logIndex", "batchIndex", "tokenId", "standard", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 0, $8, 'ERC721', NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "logIndex", "batchIndex")
+        DO UPDATE SET
+        "fromAddress" = EXCLUDED."fromAddress",
+        "toAddress" = EXCLUDED."toAddress",
+        "tokenId" = EXCLUDED."tokenId",
+        "standard" = EXCLUDED."standard"
+    "#;
+
+    // Insert the transfer, repoint the tokenId's owner (or drop it on a burn), and refresh the
+    // token's holderCount all in one transaction, same as `insert_erc20_transfer`.
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let transaction = db_client.transaction().await?;
+
+    let statement = transaction
+        .prepare(query)
+        .await
+        .expect("Failed to prepare statement");
+
+    // Prepare the parameter values
+    let params: [&(dyn ToSql + Sync); 8] = [
+        &contract_address,
+        &from_address,
+        &to_address,
+        &transaction_hash,
+        &block_number,
+        &block_hash,
+        &log_index,
+        &token_id,
+    ];
+
+    // Execute the query with parameters
+    if let Err(e) = transaction.execute(&statement, &params).await {
+        log_error!("Error inserting ERC721 transfer: {}", transaction_hash);
+        log_error!("Error: {}", e);
+        return Err(Box::new(e));
+    }
+
+    if to_address == ZERO_ADDRESS {
+        // Burn: the token no longer has an owner.
+        transaction
+            .execute(
+                r#"DELETE FROM nft_token_owners WHERE "contractAddress" = $1 AND "tokenId" = $2"#,
+                &[&contract_address, &token_id],
+            )
+            .await?;
+    } else {
+        transaction
+            .execute(
+                r#"
+                INSERT INTO nft_token_owners ("contractAddress", "tokenId", "ownerAddress", "updatedAtBlock")
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT ("contractAddress", "tokenId")
+                DO UPDATE SET
+                "ownerAddress" = EXCLUDED."ownerAddress",
+                "updatedAtBlock" = EXCLUDED."updatedAtBlock",
+                "lastUpdated" = NOW()
+                "#,
+                &[&contract_address, &token_id, &to_address, &block_number],
+            )
+            .await?;
+    }
+
+    // holderCount is the number of distinct addresses that currently own at least one tokenId of
+    // this collection.
+    let holder_count: i64 = transaction
+        .query_one(
+            r#"SELECT COUNT(DISTINCT "ownerAddress") FROM nft_token_owners WHERE "contractAddress" = $1"#,
+            &[&contract_address],
+        )
+        .await?
+        .get(0);
+
+    transaction
+        .execute(
+            r#"UPDATE tokens SET "holderCount" = $1, "totalSupplyUpdatedAtBlock" = $2 WHERE "address" = $3"#,
+            &[&(holder_count as i32), &block_number, &contract_address],
+        )
+        .await?;
+
+    transaction.commit().await?;
+    debug!("Inserted ERC721 transfer: {}", transaction_hash);
+    Ok(())
+}
@@ -1,4 +1,5 @@
 // Module: db::tokens
+use crate::db::{DbTlsConnector, SignedU256Numeric, U256Numeric};
 use crate::indexer_types;
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
@@ -7,7 +8,7 @@ use ethers_contract::Contract;
 use log::{debug, error as log_error};
 use rust_decimal::prelude::*;
 use std::{error::Error, sync::Arc};
-use tokio_postgres::{types::ToSql, NoTls};
+use tokio_postgres::types::ToSql;
 
 /// Function to insert a token into the database
 /// Here we have to get the token information from the contract
@@ -36,7 +37,7 @@ pub async fn insert_erc20_token(
     address: Address,
     verified_sc_data: indexer_types::ContractInfo,
     block_number: U64,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
     ws_client: Arc<Provider<Ws>>,
 ) -> Result<(), Box<dyn Error>> {
     debug!("Inserting ERC20 token: {}", address);
@@ -116,7 +117,12 @@ async fn get_erc20_token_data(
             U256::zero()
         }
     };
-    token_data.totalSupply = Decimal::from(total_supply.as_u128() as i64);
+    match Decimal::from_str(&total_supply.to_string()) {
+        Ok(total_supply) => token_data.totalSupply = total_supply,
+        Err(e) => {
+            log_error!("Error converting totalSupply {} to Decimal for 0x{:x}: {}", total_supply, address, e)
+        }
+    }
     // Call the name function
     let name: String = match contract.method("name", ()) {
         Ok(method) => method.call().await?,
@@ -144,32 +150,213 @@ async fn get_erc20_token_data(
         }
     };
     token_data.decimals = Decimal::from_str(decimals.to_string().as_str()).unwrap();
-    // holderCount doesn't exist in ERC20
-    // TODO: Add holderCount feature
+    // holderCount doesn't exist in ERC20; it's derived from token_balances and kept current by
+    // insert_erc20_transfer as transfers come in, so it's left at its default here.
+
+    debug!("Token data: {}", token_data.to_string());
+    Ok(token_data)
+}
+
+/// Function to insert an ERC721 token into the database.
+/// ERC-721 has no standard `decimals` (an NFT isn't divisible, so the column is left at zero) and
+/// `totalSupply` is optional in the standard, so it's best-effort like `name`/`symbol` below.
+/// Individual token ids are tracked via `token_transfers` (`tokenId`) as `Transfer` events come
+/// in, and each id's current owner in the per-token-id `nft_token_owners` table (see
+/// `token_transfers::insert_erc721_transfer`).
+pub async fn insert_erc721_token(
+    address: Address,
+    verified_sc_data: indexer_types::ContractInfo,
+    block_number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+    ws_client: Arc<Provider<Ws>>,
+) -> Result<(), Box<dyn Error>> {
+    debug!("Inserting ERC721 token: {}", address);
+    let token_data = get_erc721_token_data(address, verified_sc_data.clone(), ws_client).await?;
+    insert_token_info(address, &verified_sc_data.contractType, &token_data, block_number, db_pool)
+        .await
+}
+
+async fn get_erc721_token_data(
+    address: Address,
+    verified_sc_data: indexer_types::ContractInfo,
+    ws_client: Arc<Provider<Ws>>,
+) -> Result<indexer_types::TokenInfo, Box<dyn Error>> {
+    let mut token_data = indexer_types::TokenInfo::new();
+    let contract_abi: Abi = serde_json::from_value(verified_sc_data.abi_json).expect("Failed to parse ABI");
+    let contract = Contract::new(address, contract_abi, ws_client);
+
+    let name: String = match contract.method("name", ()) {
+        Ok(method) => method.call().await?,
+        Err(e) => {
+            log_error!("Error: {} for 0x{:x}", e, address);
+            String::from("")
+        }
+    };
+    token_data.name = name;
+
+    let symbol: String = match contract.method("symbol", ()) {
+        Ok(method) => method.call().await?,
+        Err(e) => {
+            log_error!("Error: {} for 0x{:x}", e, address);
+            String::from("")
+        }
+    };
+    token_data.symbol = symbol;
+
+    // totalSupply isn't part of the ERC-721 standard; ERC721Enumerable implementations expose it,
+    // so this is best-effort and left at zero when the contract doesn't have it.
+    if let Ok(method) = contract.method::<_, U256>("totalSupply", ()) {
+        if let Ok(total_supply) = method.call().await {
+            match Decimal::from_str(&total_supply.to_string()) {
+                Ok(total_supply) => token_data.totalSupply = total_supply,
+                Err(e) => {
+                    log_error!("Error converting totalSupply {} to Decimal for 0x{:x}: {}", total_supply, address, e)
+                }
+            }
+        }
+    }
 
     debug!("Token data: {}", token_data.to_string());
     Ok(token_data)
 }
 
+/// Function to insert an ERC1155 token into the database.
+/// ERC-1155 is multi-token by design (one contract, many token ids sharing no single
+/// `name`/`symbol`/`decimals`), so `name` is best-effort and `decimals` is left at zero. There is
+/// no `symbol()` call: `uri(uint256)` is queried with token id 0 as a representative sample and
+/// stored in the `symbol` column, since the one-row-per-contract `tokens` table has no URI column.
+pub async fn insert_erc1155_token(
+    address: Address,
+    verified_sc_data: indexer_types::ContractInfo,
+    block_number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+    ws_client: Arc<Provider<Ws>>,
+) -> Result<(), Box<dyn Error>> {
+    debug!("Inserting ERC1155 token: {}", address);
+    let token_data = get_erc1155_token_data(address, verified_sc_data.clone(), ws_client).await?;
+    insert_token_info(address, &verified_sc_data.contractType, &token_data, block_number, db_pool)
+        .await
+}
+
+async fn get_erc1155_token_data(
+    address: Address,
+    verified_sc_data: indexer_types::ContractInfo,
+    ws_client: Arc<Provider<Ws>>,
+) -> Result<indexer_types::TokenInfo, Box<dyn Error>> {
+    let mut token_data = indexer_types::TokenInfo::new();
+    let contract_abi: Abi = serde_json::from_value(verified_sc_data.abi_json).expect("Failed to parse ABI");
+    let contract = Contract::new(address, contract_abi, ws_client);
+
+    // ERC-1155 has no `name()`/`symbol()` in the standard; some collections add them anyway.
+    let name: String = match contract.method("name", ()) {
+        Ok(method) => method.call().await.unwrap_or_default(),
+        Err(_) => String::from(""),
+    };
+    token_data.name = name;
+
+    let uri: String = match contract.method("uri", U256::zero()) {
+        Ok(method) => method.call().await.unwrap_or_default(),
+        Err(_) => String::from(""),
+    };
+    token_data.symbol = uri;
+
+    debug!("Token data: {}", token_data.to_string());
+    Ok(token_data)
+}
+
+/// Shared upsert for the `tokens` table behind `insert_erc721_token`/`insert_erc1155_token`
+/// (`insert_erc20_token` runs its own upsert): only `holderCount` is standard-specific enough to need its own
+/// maintenance path (via `insert_erc20_transfer`/`insert_erc721_transfer`), so it's left
+/// untouched here rather than reset to zero on every re-index.
+async fn insert_token_info(
+    address: Address,
+    contract_type: &str,
+    token_data: &indexer_types::TokenInfo,
+    block_number: U64,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    let address = format!("0x{:x}", address);
+    let query = r#"
+        INSERT INTO tokens
+        ("address", "type", "name", "symbol", "totalSupply", "decimals", "totalSupplyUpdatedAtBlock", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+        ON CONFLICT (address)
+        DO UPDATE SET
+        "name" = EXCLUDED."name",
+        "symbol" = EXCLUDED."symbol",
+        "totalSupply" = EXCLUDED."totalSupply",
+        "decimals" = EXCLUDED."decimals",
+        "totalSupplyUpdatedAtBlock" = EXCLUDED."totalSupplyUpdatedAtBlock"
+    "#;
+
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let statement = db_client
+        .prepare(query)
+        .await
+        .expect("Failed to prepare statement");
+    let params: [&(dyn ToSql + Sync); 7] = [
+        &address,
+        &contract_type,
+        &token_data.name,
+        &token_data.symbol,
+        &token_data.totalSupply,
+        &token_data.decimals,
+        &(block_number.as_u64() as i64),
+    ];
+    let result = db_client.execute(&statement, &params).await;
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log_error!("Error inserting token: {}", address);
+            log_error!("Error: {}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
 /// Function to insert a token transfer into the database
 /// Database schema:
 /// CREATE TABLE "token_transfers" (
 ///     "contractAddress" VARCHAR(42) NOT NULL,
 ///     "fromAddress" VARCHAR(42),
 ///     "toAddress" VARCHAR(42),
+///     "operatorAddress" VARCHAR(42),
 ///     "transactionHash" VARCHAR(66) NOT NULL,
 ///     "blockNumber" BIGINT NOT NULL,
 ///     "blockHash" VARCHAR(66),
 ///     "logIndex" integer NOT NULL,
+///     "batchIndex" integer NOT NULL DEFAULT 0,
+///     "tokenId" NUMERIC(100),
 ///     "amount" NUMERIC(100),
+///     "standard" VARCHAR(10),
 ///     "insertedAt" timestamp,
 ///     "lastUpdated" timestamp default current_timestamp,
-///     CONSTRAINT token_transfers_pkey PRIMARY KEY ("transactionHash", "blockHash", "logIndex")
+///     CONSTRAINT token_transfers_pkey PRIMARY KEY ("transactionHash", "blockHash", "logIndex", "batchIndex")
+/// );
+/// `operatorAddress`/`batchIndex` only apply to ERC-1155, and `tokenId` to ERC-721/ERC-1155
+/// (ERC-721 keeps its transferred id there, with `amount` left NULL): `operatorAddress` is the
+/// account that triggered the transfer (which can differ from `fromAddress` when approved), and
+/// `batchIndex` is a `TransferBatch` event's position within its `ids`/`values` arrays (always 0
+/// for ERC-20/ERC-721 and ERC-1155's own `TransferSingle`, since those are one row per log).
+/// Function to insert an ERC20 transfer and fold its effect into `token_balances`
+/// Database schema:
+/// CREATE TABLE "token_balances" (
+///     "contractAddress" VARCHAR(42) NOT NULL,
+///     "holderAddress" VARCHAR(42) NOT NULL,
+///     "balance" NUMERIC(100) NOT NULL DEFAULT 0,
+///     "lastUpdated" timestamp default current_timestamp,
+///     CONSTRAINT token_balances_pkey PRIMARY KEY ("contractAddress", "holderAddress")
 /// );
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
 pub async fn insert_erc20_transfer(
     log: Log,
     decoded_log: indexer_types::Transfer,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), Box<dyn Error>> {
     debug!("Inserting ERC20 transfer: {:?}", log);
 
@@ -181,26 +368,41 @@ pub async fn insert_erc20_transfer(
     let block_hash = format!("0x{:x}", log.block_hash.unwrap());
     let block_number = log.block_number.unwrap().as_u64() as i64;
     let log_index = log.log_index.unwrap().as_u64() as i32;
-    let amount = Decimal::from(decoded_log.value.as_u128() as i64);
+    // `token_transfers.amount` is the raw (always non-negative) transfer value, so it binds
+    // straight through `U256Numeric`; the signed `token_balances` delta below needs
+    // `SignedU256Numeric` instead, since `rust_decimal::Decimal`'s ~96-bit mantissa overflows for
+    // the highest-supply ERC-20s (a `value` near `U256::MAX` is a ~78-digit number) and a `?` on
+    // that overflow would abort the whole transfer instead of just mis-sizing one balance.
+    let amount = U256Numeric::from(decoded_log.value);
 
     // Build the SQL query
+    // `xmax = 0` is Postgres's tell for "this row was just inserted, not updated by the ON
+    // CONFLICT arm" — a replayed log (reorg re-index, gap backfill, failed-block retry) hits the
+    // DO UPDATE branch instead, and `inserted` comes back false so the balance delta below isn't
+    // re-applied on top of a balance it already updated.
     let query = r#"
-        INSERT INTO token_transfers 
-        ("contractAddress", "fromAddress", "toAddress", "transactionHash", "blockNumber", "blockHash", "logIndex", "amount", "insertedAt") 
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW()) 
-        ON CONFLICT ("transactionHash", "blockHash", "logIndex") 
-        DO UPDATE SET 
+        INSERT INTO token_transfers
+        ("contractAddress", "fromAddress", "toAddress", "transactionHash", "blockNumber", "blockHash", "logIndex", "batchIndex", "amount", "standard", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 0, $8, 'ERC20', NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "logIndex", "batchIndex")
+        DO UPDATE SET
         "fromAddress" = EXCLUDED."fromAddress",
         "toAddress" = EXCLUDED."toAddress",
-        "amount" = EXCLUDED."amount"
+        "amount" = EXCLUDED."amount",
+        "standard" = EXCLUDED."standard"
+        RETURNING (xmax = 0) AS inserted
     "#;
 
-    // Prepare the statement
-    let db_client = db_pool.get().await.map_err(|e| {
+    // Insert the transfer, apply its balance delta, and refresh the token's holderCount all in
+    // one transaction so a crash mid-update can't leave the balances out of sync with the
+    // transfer log.
+    let mut db_client = db_pool.get().await.map_err(|e| {
         log_error!("Error acquiring database connection: {}", e);
         Box::new(e) as Box<dyn Error>
     })?;
-    let statement = db_client
+    let transaction = db_client.transaction().await?;
+
+    let statement = transaction
         .prepare(query)
         .await
         .expect("Failed to prepare statement");
@@ -218,15 +420,298 @@ pub async fn insert_erc20_transfer(
     ];
 
     // Execute the query with parameters
-    let result = db_client.execute(&statement, &params).await;
+    let inserted: bool = match transaction.query_one(&statement, &params).await {
+        Ok(row) => row.get("inserted"),
+        Err(e) => {
+            log_error!("Error inserting ERC20 transfer: {}", transaction_hash);
+            log_error!("Error: {}", e);
+            return Err(Box::new(e));
+        }
+    };
 
-    match result {
+    if inserted {
+        // Credit the receiver and debit the sender, skipping the zero address since mints and
+        // burns don't have a real holder on that side of the transfer.
+        for (holder_address, delta) in [
+            (&from_address, SignedU256Numeric::negative(decoded_log.value)),
+            (&to_address, SignedU256Numeric::positive(decoded_log.value)),
+        ] {
+            if holder_address == ZERO_ADDRESS {
+                continue;
+            }
+            transaction
+                .execute(
+                    r#"
+                    INSERT INTO token_balances ("contractAddress", "holderAddress", "balance")
+                    VALUES ($1, $2, $3)
+                    ON CONFLICT ("contractAddress", "holderAddress")
+                    DO UPDATE SET
+                    "balance" = token_balances."balance" + EXCLUDED."balance",
+                    "lastUpdated" = NOW()
+                    "#,
+                    &[&contract_address, holder_address, &delta],
+                )
+                .await?;
+        }
+
+        // holderCount is the number of addresses still holding a non-zero balance of this token.
+        let holder_count: i64 = transaction
+            .query_one(
+                r#"SELECT COUNT(*) FROM token_balances WHERE "contractAddress" = $1 AND "balance" <> 0"#,
+                &[&contract_address],
+            )
+            .await?
+            .get(0);
+
+        transaction
+            .execute(
+                r#"UPDATE tokens SET "holderCount" = $1, "totalSupplyUpdatedAtBlock" = $2 WHERE "address" = $3"#,
+                &[&(holder_count as i32), &block_number, &contract_address],
+            )
+            .await?;
+    }
+
+    transaction.commit().await?;
+    debug!("Inserted ERC20 transfer: {}", transaction_hash);
+    Ok(())
+}
+
+/// Insert one expanded row of an ERC-1155 transfer: `TransferSingle` calls this once with
+/// `batch_index` 0, `TransferBatch` calls it once per `(id, value)` pair in its `ids`/`values`
+/// arrays with `batch_index` set to that pair's position. Unlike `insert_erc20_transfer`, this
+/// doesn't maintain `token_balances`/`holderCount`: those are keyed per-contract, not per-token,
+/// and ERC-1155 balances are per `(contractAddress, tokenId, holderAddress)`, which is out of
+/// scope here.
+pub async fn insert_erc1155_transfer(
+    log: &Log,
+    operator: Address,
+    from: Address,
+    to: Address,
+    id: U256,
+    value: U256,
+    batch_index: i32,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    debug!("Inserting ERC1155 transfer: {:?} (batch index {})", log, batch_index);
+
+    let contract_address = format!("0x{:x}", log.address);
+    let operator_address = format!("0x{:x}", operator);
+    let from_address = format!("0x{:x}", from);
+    let to_address = format!("0x{:x}", to);
+    let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+    let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+    let block_number = log.block_number.unwrap().as_u64() as i64;
+    let log_index = log.log_index.unwrap().as_u64() as i32;
+    let token_id = U256Numeric::from(id);
+    let amount = U256Numeric::from(value);
+
+    let query = r#"
+        INSERT INTO token_transfers
+        ("contractAddress", "fromAddress", "toAddress", "operatorAddress", "transactionHash", "blockNumber", "blockHash", "logIndex", "batchIndex", "tokenId", "amount", "standard", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, 'ERC1155', NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "logIndex", "batchIndex")
+        DO UPDATE SET
+        "fromAddress" = EXCLUDED."fromAddress",
+        "toAddress" = EXCLUDED."toAddress",
+        "operatorAddress" = EXCLUDED."operatorAddress",
+        "tokenId" = EXCLUDED."tokenId",
+        "amount" = EXCLUDED."amount",
+        "standard" = EXCLUDED."standard"
+    "#;
+
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let statement = db_client
+        .prepare(query)
+        .await
+        .expect("Failed to prepare statement");
+
+    let params: [&(dyn ToSql + Sync); 11] = [
+        &contract_address,
+        &from_address,
+        &to_address,
+        &operator_address,
+        &transaction_hash,
+        &block_number,
+        &block_hash,
+        &log_index,
+        &batch_index,
+        &token_id,
+        &amount,
+    ];
+
+    match db_client.execute(&statement, &params).await {
         Ok(_) => {
-            debug!("Inserted ERC20 transfer: {}", transaction_hash);
+            debug!("Inserted ERC1155 transfer: {} (batch index {})", transaction_hash, batch_index);
             Ok(())
         }
         Err(e) => {
-            log_error!("Error inserting ERC20 transfer: {}", transaction_hash);
+            log_error!("Error inserting ERC1155 transfer: {} (batch index {})", transaction_hash, batch_index);
+            log_error!("Error: {}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+/// Insert an ERC-20/ERC-721 `Approval(owner, spender, value)` into `token_approvals`, upserting on
+/// the same `(transactionHash, blockHash, logIndex)` key `token_transfers` uses so a re-orged
+/// block's approvals overwrite rather than duplicate.
+///
+/// Database schema:
+/// CREATE TABLE "token_approvals" (
+///     "contractAddress" VARCHAR(42) NOT NULL,
+///     "ownerAddress" VARCHAR(42) NOT NULL,
+///     "spenderAddress" VARCHAR(42),
+///     "operatorAddress" VARCHAR(42),
+///     "tokenId" NUMERIC(100),
+///     "amount" NUMERIC(100),
+///     "approved" BOOLEAN,
+///     "eventType" VARCHAR(20) NOT NULL,
+///     "standard" VARCHAR(10),
+///     "transactionHash" VARCHAR(66) NOT NULL,
+///     "blockNumber" BIGINT NOT NULL,
+///     "blockHash" VARCHAR(66),
+///     "logIndex" integer NOT NULL,
+///     "insertedAt" timestamp,
+///     "lastUpdated" timestamp default current_timestamp,
+///     CONSTRAINT token_approvals_pkey PRIMARY KEY ("transactionHash", "blockHash", "logIndex")
+/// );
+///
+/// `standard` is `'ERC20'` or `'ERC721'` (`token_approvals::index_approvals` tells them apart the
+/// same way it tells `Transfer` apart: topic count); the approved `value` is stored in `amount`
+/// for ERC-20 and in `tokenId` for ERC-721, the other left NULL. `"eventType"` is always
+/// `'Approval'` here, distinguishing these rows from `insert_approval_for_all`'s.
+pub async fn insert_approval(
+    log: &Log,
+    decoded_log: indexer_types::Approval,
+    standard: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    debug!("Inserting {} Approval: {:?}", standard, log);
+
+    let contract_address = format!("0x{:x}", log.address);
+    let owner_address = format!("0x{:x}", decoded_log.owner);
+    let spender_address = format!("0x{:x}", decoded_log.spender);
+    let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+    let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+    let block_number = log.block_number.unwrap().as_u64() as i64;
+    let log_index = log.log_index.unwrap().as_u64() as i32;
+
+    let (token_id, amount): (Option<U256Numeric>, Option<U256Numeric>) = if standard == "ERC721" {
+        (Some(U256Numeric::from(decoded_log.value)), None)
+    } else {
+        (None, Some(U256Numeric::from(decoded_log.value)))
+    };
+
+    let query = r#"
+        INSERT INTO token_approvals
+        ("contractAddress", "ownerAddress", "spenderAddress", "tokenId", "amount", "eventType", "standard", "transactionHash", "blockNumber", "blockHash", "logIndex", "insertedAt")
+        VALUES ($1, $2, $3, $4, $5, 'Approval', $6, $7, $8, $9, $10, NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "logIndex")
+        DO UPDATE SET
+        "ownerAddress" = EXCLUDED."ownerAddress",
+        "spenderAddress" = EXCLUDED."spenderAddress",
+        "tokenId" = EXCLUDED."tokenId",
+        "amount" = EXCLUDED."amount",
+        "standard" = EXCLUDED."standard",
+        "lastUpdated" = NOW()
+    "#;
+
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let statement = db_client
+        .prepare(query)
+        .await
+        .expect("Failed to prepare statement");
+
+    let params: [&(dyn ToSql + Sync); 10] = [
+        &contract_address,
+        &owner_address,
+        &spender_address,
+        &token_id,
+        &amount,
+        &standard,
+        &transaction_hash,
+        &block_number,
+        &block_hash,
+        &log_index,
+    ];
+
+    match db_client.execute(&statement, &params).await {
+        Ok(_) => {
+            debug!("Inserted {} Approval: {}", standard, transaction_hash);
+            Ok(())
+        }
+        Err(e) => {
+            log_error!("Error inserting {} Approval: {}", standard, transaction_hash);
+            log_error!("Error: {}", e);
+            Err(Box::new(e))
+        }
+    }
+}
+
+/// Insert an ERC-721/ERC-1155 `ApprovalForAll(owner, operator, approved)` into `token_approvals`.
+/// See [`insert_approval`]'s doc comment for the shared table schema; `standard` is left NULL here
+/// since the event itself doesn't disambiguate ERC-721 from ERC-1155, and `"eventType"` is always
+/// `'ApprovalForAll'`.
+pub async fn insert_approval_for_all(
+    log: &Log,
+    decoded_log: indexer_types::ApprovalForAll,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    debug!("Inserting ApprovalForAll: {:?}", log);
+
+    let contract_address = format!("0x{:x}", log.address);
+    let owner_address = format!("0x{:x}", decoded_log.owner);
+    let operator_address = format!("0x{:x}", decoded_log.operator);
+    let transaction_hash = format!("0x{:x}", log.transaction_hash.unwrap());
+    let block_hash = format!("0x{:x}", log.block_hash.unwrap());
+    let block_number = log.block_number.unwrap().as_u64() as i64;
+    let log_index = log.log_index.unwrap().as_u64() as i32;
+
+    let query = r#"
+        INSERT INTO token_approvals
+        ("contractAddress", "ownerAddress", "operatorAddress", "approved", "eventType", "transactionHash", "blockNumber", "blockHash", "logIndex", "insertedAt")
+        VALUES ($1, $2, $3, $4, 'ApprovalForAll', $5, $6, $7, $8, NOW())
+        ON CONFLICT ("transactionHash", "blockHash", "logIndex")
+        DO UPDATE SET
+        "ownerAddress" = EXCLUDED."ownerAddress",
+        "operatorAddress" = EXCLUDED."operatorAddress",
+        "approved" = EXCLUDED."approved",
+        "lastUpdated" = NOW()
+    "#;
+
+    let db_client = db_pool.get().await.map_err(|e| {
+        log_error!("Error acquiring database connection: {}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+    let statement = db_client
+        .prepare(query)
+        .await
+        .expect("Failed to prepare statement");
+
+    let params: [&(dyn ToSql + Sync); 8] = [
+        &contract_address,
+        &owner_address,
+        &operator_address,
+        &decoded_log.approved,
+        &transaction_hash,
+        &block_number,
+        &block_hash,
+        &log_index,
+    ];
+
+    match db_client.execute(&statement, &params).await {
+        Ok(_) => {
+            debug!("Inserted ApprovalForAll: {}", transaction_hash);
+            Ok(())
+        }
+        Err(e) => {
+            log_error!("Error inserting ApprovalForAll: {}", transaction_hash);
             log_error!("Error: {}", e);
             Err(Box::new(e))
         }
@@ -1,12 +1,55 @@
 // Module: db::transactions
 
+use crate::abi_decoder;
+use crate::db::{contracts, DbTlsConnector, U256Numeric};
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
 use ethers::prelude::*;
-use rust_decimal::prelude::*;
+use ethers::types::OtherFields;
+use futures::pin_mut;
+use log::error as log_error;
 use serde_json;
 use std::error::Error;
-use tokio_postgres::{types::ToSql, NoTls};
+use tokio_postgres::{binary_copy::BinaryCopyInWriter, types::ToSql, types::Type};
+
+/// Decode `transaction.input` against its `to` address's verified ABI, if any.
+/// Returns `None` (stored as SQL `NULL`) whenever `to` is absent (contract creation), ruled out by
+/// [`crate::token_filter::should_index`], unverified, or no function in its ABI matches the
+/// input's selector.
+async fn decode_input(
+    transaction: &Transaction,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Option<serde_json::Value> {
+    let to = transaction.to?;
+    if !crate::token_filter::should_index(to) {
+        return None;
+    }
+    let abi_json = match contracts::get_abi_json(to, db_pool).await {
+        Ok(abi_json) => abi_json?,
+        Err(e) => {
+            log_error!("Error fetching ABI for {:?}: {}", to, e);
+            return None;
+        }
+    };
+
+    let (function, params) =
+        abi_decoder::decode_function_input(to, &abi_json, &transaction.input)?;
+    Some(serde_json::json!({
+        "function": function,
+        "params": abi_decoder::params_to_json(&params),
+    }))
+}
+
+/// Read a hex-string-encoded `U256` out of a JSON-RPC response's catch-all `other` fields.
+///
+/// ethers-rs doesn't model EIP-4844 blob fields (`maxFeePerBlobGas`, `blobGasUsed`,
+/// `blobGasPrice`) on `Transaction`/`TransactionReceipt` yet, so they only ever show up here.
+/// Returns `None` if the field is absent (every transaction/receipt type before type 3) or not a
+/// well-formed hex integer.
+fn other_u256(other: &OtherFields, key: &str) -> Option<U256> {
+    let value = other.get(key)?.as_str()?;
+    U256::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
 
 /// Function to insert a transaction into the database
 /// Database schema:
@@ -30,12 +73,25 @@ use tokio_postgres::{types::ToSql, NoTls};
 /// "maxFeePerGas" NUMERIC(100),
 /// "transactionIndex" INT NOT NULL,
 /// "maxPriorityFeePerGas" NUMERIC(100),
+/// "maxFeePerBlobGas" NUMERIC(100),
+/// "blobVersionedHashes" JSON,
+/// "decodedInput" JSON,
 /// "lastUpdated" timestamp default current_timestamp,
 /// FOREIGN KEY ("blockNumber") REFERENCES blocks("number") ON DELETE CASCADE
 /// );
+///
+/// `"type"` is `0` (legacy) whenever `transaction_type` isn't reported, and `"gasPrice"`,
+/// `"maxFeePerGas"`/`"maxPriorityFeePerGas"`, and the blob columns are left `NULL` rather than
+/// `0` whenever the envelope the transaction actually uses doesn't carry them (see EIP-2718's
+/// type registry: legacy/type-1 only ever set `gasPrice`, type-2/3 only ever set the `max*`
+/// fields, and only type-3 sets the blob fields).
+///
+/// `"decodedInput"` is `{"function": ..., "params": [...]}` decoded via
+/// [`crate::abi_decoder::decode_function_input`] against `"to"`'s verified ABI, or `null` when
+/// `to` isn't a verified contract or no function in its ABI matches `input`'s selector.
 pub async fn insert_transaction(
     transaction: Transaction,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), Box<dyn Error>> {
     // Extract relevant data from the transaction
     let r = format!("0x{:x}", transaction.r);
@@ -45,32 +101,41 @@ pub async fn insert_transaction(
     let gas = transaction.gas.as_u64() as i32;
     let from = format!("0x{:x}", transaction.from);
     let hash = format!("0x{:x}", transaction.hash());
-    let transaction_type = transaction.transaction_type.unwrap().as_u64() as i16;
+    let transaction_type = transaction
+        .transaction_type
+        .map(|t| t.as_u64() as i16)
+        .unwrap_or(0);
     let input = format!("{:x}", transaction.input);
     let nonce = transaction.nonce.as_u64() as i32;
-    let value = Decimal::from(transaction.value.as_u128() as i64);
-    let chain_id = transaction.chain_id.unwrap().as_u64().to_string();
-    let gas_price = Decimal::from(transaction.gas_price.unwrap().as_u128() as i64);
+    let value = U256Numeric::from(transaction.value);
+    let chain_id = transaction.chain_id.map(|id| id.as_u64().to_string());
+    // Type-2 and later envelopes have no `gasPrice` field; a value a node reports there for them
+    // (some fill in the effective price) is not stored.
+    let gas_price = if transaction_type >= 2 {
+        None
+    } else {
+        transaction.gas_price.map(U256Numeric::from)
+    };
     let block_hash = format!("0x{:x}", transaction.block_hash.unwrap());
     let access_list = serde_json::to_value(&transaction.access_list).unwrap();
     let block_number = transaction.block_number.unwrap().as_u64() as i64;
-    let max_fee_per_gas =
-        Decimal::from(transaction.max_fee_per_gas.unwrap_or_default().as_u128() as i64);
+    let max_fee_per_gas = transaction.max_fee_per_gas.map(U256Numeric::from);
     let transaction_index = transaction.transaction_index.unwrap_or_default().as_u64() as i32;
-    let max_priority_fee_per_gas = Decimal::from(
-        transaction
-            .max_priority_fee_per_gas
-            .unwrap_or_default()
-            .as_u128() as i64,
-    );
+    let max_priority_fee_per_gas = transaction.max_priority_fee_per_gas.map(U256Numeric::from);
+    let max_fee_per_blob_gas =
+        other_u256(&transaction.other, "maxFeePerBlobGas").map(U256Numeric::from);
+    let blob_versioned_hashes = transaction.other.get("blobVersionedHashes").cloned();
+    let decoded_input = decode_input(&transaction, db_pool.clone()).await;
 
     // Build the SQL query
     let query = r#"
         INSERT INTO transactions ("r", "s", "v", "to", "gas", "from", "hash", "type", "input",
                                   "nonce", "value", "chainId", "gasPrice", "blockHash",
                                   "accessList", "blockNumber", "maxFeePerGas", "transactionIndex",
-                                  "maxPriorityFeePerGas")
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14 ,$15, $16, $17, $18, $19)
+                                  "maxPriorityFeePerGas", "maxFeePerBlobGas", "blobVersionedHashes",
+                                  "decodedInput")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14 ,$15, $16, $17, $18,
+                $19, $20, $21, $22)
         ON CONFLICT ("hash") DO NOTHING;
     "#;
     // Prepare the statement
@@ -83,7 +148,7 @@ pub async fn insert_transaction(
         .await
         .expect("Failed to prepare statement");
     // Prepare the parameter values
-    let params: [&(dyn ToSql + Sync); 19] = [
+    let params: [&(dyn ToSql + Sync); 22] = [
         &r,
         &s,
         &v,
@@ -103,6 +168,9 @@ pub async fn insert_transaction(
         &max_fee_per_gas,
         &transaction_index,
         &max_priority_fee_per_gas,
+        &max_fee_per_blob_gas,
+        &blob_versioned_hashes,
+        &decoded_input,
     ];
 
     // Execute the query with parameters
@@ -120,6 +188,285 @@ pub async fn insert_transaction(
     }
 }
 
+/// Bulk-insert a batch of transactions using Postgres binary `COPY`.
+///
+/// Mirrors [`crate::db::insert_blocks_bulk`]: rows are streamed into a per-connection temp
+/// table via `COPY ... FROM STDIN BINARY` and folded into `transactions` with a single
+/// `INSERT ... SELECT ... ON CONFLICT DO NOTHING`, so a whole `index_blocks` batch flushes in
+/// one round trip instead of one `execute()` per transaction.
+pub async fn insert_transactions_bulk(
+    transactions: Vec<Transaction>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    if transactions.is_empty() {
+        return Ok(());
+    }
+
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        eprintln!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+
+    let transaction = db_client.transaction().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            CREATE TEMP TABLE temp_transactions (
+                "r" VARCHAR(66), "s" VARCHAR(66), "v" VARCHAR(4), "to" VARCHAR(42), "gas" INT,
+                "from" VARCHAR(42), "hash" VARCHAR(66), "type" SMALLINT, "input" TEXT,
+                "nonce" INT, "value" NUMERIC(100), "chainId" VARCHAR(10), "gasPrice" NUMERIC(100),
+                "blockHash" VARCHAR(66), "accessList" JSON, "blockNumber" BIGINT,
+                "maxFeePerGas" NUMERIC(100), "transactionIndex" INT,
+                "maxPriorityFeePerGas" NUMERIC(100), "maxFeePerBlobGas" NUMERIC(100),
+                "blobVersionedHashes" JSON
+            ) ON COMMIT DROP;
+            "#,
+        )
+        .await?;
+
+    let sink = transaction
+        .copy_in(
+            r#"COPY temp_transactions ("r", "s", "v", "to", "gas", "from", "hash", "type",
+                "input", "nonce", "value", "chainId", "gasPrice", "blockHash", "accessList",
+                "blockNumber", "maxFeePerGas", "transactionIndex", "maxPriorityFeePerGas",
+                "maxFeePerBlobGas", "blobVersionedHashes")
+                FROM STDIN BINARY"#,
+        )
+        .await?;
+    let writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::INT4,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::INT2,
+            Type::TEXT,
+            Type::INT4,
+            Type::NUMERIC,
+            Type::VARCHAR,
+            Type::NUMERIC,
+            Type::VARCHAR,
+            Type::JSON,
+            Type::INT8,
+            Type::NUMERIC,
+            Type::INT4,
+            Type::NUMERIC,
+            Type::NUMERIC,
+            Type::JSON,
+        ],
+    );
+    pin_mut!(writer);
+
+    for tx in &transactions {
+        let r = format!("0x{:x}", tx.r);
+        let s = format!("0x{:x}", tx.s);
+        let v = format!("0x{:x}", tx.v);
+        let to = format!("0x{:x}", tx.to.unwrap_or_default());
+        let gas = tx.gas.as_u64() as i32;
+        let from = format!("0x{:x}", tx.from);
+        let hash = format!("0x{:x}", tx.hash());
+        let transaction_type = tx.transaction_type.map(|t| t.as_u64() as i16).unwrap_or(0);
+        let input = format!("{:x}", tx.input);
+        let nonce = tx.nonce.as_u64() as i32;
+        let value = U256Numeric::from(tx.value);
+        let chain_id = tx.chain_id.map(|id| id.as_u64().to_string());
+        // Same `gasPrice` rule as `insert_transaction`: left NULL for type-2 and later envelopes.
+        let gas_price = if transaction_type >= 2 {
+            None
+        } else {
+            tx.gas_price.map(U256Numeric::from)
+        };
+        let block_hash = format!("0x{:x}", tx.block_hash.unwrap());
+        let access_list = serde_json::to_value(&tx.access_list).unwrap();
+        let block_number = tx.block_number.unwrap().as_u64() as i64;
+        let max_fee_per_gas = tx.max_fee_per_gas.map(U256Numeric::from);
+        let transaction_index = tx.transaction_index.unwrap_or_default().as_u64() as i32;
+        let max_priority_fee_per_gas = tx.max_priority_fee_per_gas.map(U256Numeric::from);
+        let max_fee_per_blob_gas =
+            other_u256(&tx.other, "maxFeePerBlobGas").map(U256Numeric::from);
+        let blob_versioned_hashes = tx.other.get("blobVersionedHashes").cloned();
+
+        writer
+            .as_mut()
+            .write(&[
+                &r,
+                &s,
+                &v,
+                &to,
+                &gas,
+                &from,
+                &hash,
+                &transaction_type,
+                &input,
+                &nonce,
+                &value,
+                &chain_id,
+                &gas_price,
+                &block_hash,
+                &access_list,
+                &block_number,
+                &max_fee_per_gas,
+                &transaction_index,
+                &max_priority_fee_per_gas,
+                &max_fee_per_blob_gas,
+                &blob_versioned_hashes,
+            ])
+            .await?;
+    }
+    writer.finish().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            INSERT INTO transactions ("r", "s", "v", "to", "gas", "from", "hash", "type",
+                "input", "nonce", "value", "chainId", "gasPrice", "blockHash", "accessList",
+                "blockNumber", "maxFeePerGas", "transactionIndex", "maxPriorityFeePerGas",
+                "maxFeePerBlobGas", "blobVersionedHashes")
+            SELECT * FROM temp_transactions
+            ON CONFLICT ("hash") DO NOTHING;
+            "#,
+        )
+        .await?;
+
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Bulk-insert a batch of transaction receipts using Postgres binary `COPY`.
+///
+/// Mirrors [`insert_transactions_bulk`]: rows are streamed into a per-connection temp table via
+/// `COPY ... FROM STDIN BINARY` and folded into `transactions_receipts` with a single
+/// `INSERT ... SELECT ... ON CONFLICT DO NOTHING`, so a whole batch's receipts flush in one round
+/// trip instead of one `prepare()` + `execute()` per transaction.
+pub async fn insert_transaction_receipts_bulk(
+    receipts: Vec<TransactionReceipt>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), Box<dyn Error>> {
+    if receipts.is_empty() {
+        return Ok(());
+    }
+
+    let mut db_client = db_pool.get().await.map_err(|e| {
+        eprintln!("Error acquiring database connection: {:?}", e);
+        Box::new(e) as Box<dyn Error>
+    })?;
+
+    let transaction = db_client.transaction().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            CREATE TEMP TABLE temp_transactions_receipts (
+                "transactionHash" VARCHAR(66), "transactionIndex" INT, "blockHash" VARCHAR(66),
+                "from" VARCHAR(42), "to" VARCHAR(42), "blockNumber" BIGINT,
+                "cumulativeGasUsed" NUMERIC(100), "gasUsed" NUMERIC(100),
+                "contractAddress" VARCHAR(42), "logs" JSON, "logsBloom" TEXT, "status" BOOLEAN,
+                "effectiveGasPrice" NUMERIC(100), "type" VARCHAR(10), "blobGasUsed" NUMERIC(100),
+                "blobGasPrice" NUMERIC(100)
+            ) ON COMMIT DROP;
+            "#,
+        )
+        .await?;
+
+    let sink = transaction
+        .copy_in(
+            r#"COPY temp_transactions_receipts ("transactionHash", "transactionIndex",
+                "blockHash", "from", "to", "blockNumber", "cumulativeGasUsed", "gasUsed",
+                "contractAddress", "logs", "logsBloom", "status", "effectiveGasPrice", "type",
+                "blobGasUsed", "blobGasPrice")
+                FROM STDIN BINARY"#,
+        )
+        .await?;
+    let writer = BinaryCopyInWriter::new(
+        sink,
+        &[
+            Type::VARCHAR,
+            Type::INT4,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::VARCHAR,
+            Type::INT8,
+            Type::NUMERIC,
+            Type::NUMERIC,
+            Type::VARCHAR,
+            Type::JSON,
+            Type::TEXT,
+            Type::BOOL,
+            Type::NUMERIC,
+            Type::VARCHAR,
+            Type::NUMERIC,
+            Type::NUMERIC,
+        ],
+    );
+    pin_mut!(writer);
+
+    for receipt in &receipts {
+        let transaction_hash = format!("0x{:x}", receipt.transaction_hash);
+        let transaction_index = receipt.transaction_index.as_u64() as i32;
+        let block_hash = format!("0x{:x}", receipt.block_hash.unwrap());
+        let from = format!("0x{:x}", receipt.from);
+        let to = format!("0x{:x}", receipt.to.unwrap_or_default());
+        let block_number = receipt.block_number.unwrap().as_u64() as i64;
+        let cumulative_gas_used = U256Numeric::from(receipt.cumulative_gas_used);
+        let gas_used = U256Numeric::from(receipt.gas_used.unwrap_or_default());
+        let contract_address = format!("0x{:x}", receipt.contract_address.unwrap_or_default());
+        let logs = serde_json::to_value(&receipt.logs).unwrap();
+        let logs_bloom = format!("0x{:x}", receipt.logs_bloom);
+        let status = receipt.status.unwrap_or_default().as_u32() == 1;
+        let effective_gas_price = U256Numeric::from(receipt.effective_gas_price.unwrap_or_default());
+        let transaction_type = format!(
+            "{:?}",
+            receipt.transaction_type.unwrap_or_default()
+        );
+        let blob_gas_used = other_u256(&receipt.other, "blobGasUsed").map(U256Numeric::from);
+        let blob_gas_price = other_u256(&receipt.other, "blobGasPrice").map(U256Numeric::from);
+
+        writer
+            .as_mut()
+            .write(&[
+                &transaction_hash,
+                &transaction_index,
+                &block_hash,
+                &from,
+                &to,
+                &block_number,
+                &cumulative_gas_used,
+                &gas_used,
+                &contract_address,
+                &logs,
+                &logs_bloom,
+                &status,
+                &effective_gas_price,
+                &transaction_type,
+                &blob_gas_used,
+                &blob_gas_price,
+            ])
+            .await?;
+    }
+    writer.finish().await?;
+
+    transaction
+        .batch_execute(
+            r#"
+            INSERT INTO transactions_receipts ("transactionHash", "transactionIndex", "blockHash",
+                "from", "to", "blockNumber", "cumulativeGasUsed", "gasUsed", "contractAddress",
+                "logs", "logsBloom", "status", "effectiveGasPrice", "type", "blobGasUsed",
+                "blobGasPrice")
+            SELECT * FROM temp_transactions_receipts
+            ON CONFLICT ("transactionHash") DO NOTHING;
+            "#,
+        )
+        .await?;
+
+    transaction.commit().await?;
+    Ok(())
+}
+
 /// Function to insert a transaction receipt into the database
 /// Database schema:
 /// CREATE TABLE transactions_receipts (
@@ -137,13 +484,15 @@ pub async fn insert_transaction(
 /// "status" BOOLEAN,
 /// "effectiveGasPrice" VARCHAR(78),
 /// "type" VARCHAR(10),
+/// "blobGasUsed" NUMERIC(100),
+/// "blobGasPrice" NUMERIC(100),
 /// "lastUpdated" timestamp default current_timestamp,
 /// FOREIGN KEY ("blockNumber") REFERENCES blocks("number") ON DELETE CASCADE,
 /// FOREIGN KEY ("transactionHash") REFERENCES transactions("hash") ON DELETE CASCADE
 /// );
 pub async fn insert_transaction_receipt(
     transaction_receipt: TransactionReceipt,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), Box<dyn Error>> {
     // Extract relevant data from the transaction
     let transaction_hash = format!("0x{:x}", transaction_receipt.transaction_hash);
@@ -152,8 +501,8 @@ pub async fn insert_transaction_receipt(
     let from = format!("0x{:x}", transaction_receipt.from);
     let to = format!("0x{:x}", transaction_receipt.to.unwrap_or_default());
     let block_number = transaction_receipt.block_number.unwrap().as_u64() as i64;
-    let cumulative_gas_used = Decimal::from(transaction_receipt.cumulative_gas_used.as_u128());
-    let gas_used = Decimal::from(transaction_receipt.gas_used.unwrap_or_default().as_u128() as i64);
+    let cumulative_gas_used = U256Numeric::from(transaction_receipt.cumulative_gas_used);
+    let gas_used = U256Numeric::from(transaction_receipt.gas_used.unwrap_or_default());
     let contract_address = format!(
         "0x{:x}",
         transaction_receipt.contract_address.unwrap_or_default()
@@ -165,21 +514,26 @@ pub async fn insert_transaction_receipt(
     } else {
         false
     };
-    let effective_gas_price = Decimal::from(
-        transaction_receipt
-            .effective_gas_price
-            .unwrap_or_default()
-            .as_u128(),
+    let effective_gas_price =
+        U256Numeric::from(transaction_receipt.effective_gas_price.unwrap_or_default());
+    // `transaction_type` is only absent for legacy receipts served by pre-Berlin nodes; treat
+    // that the same as an explicit type 0.
+    let transaction_type = format!(
+        "{:?}",
+        transaction_receipt.transaction_type.unwrap_or_default()
     );
-    let transaction_type = format!("{:?}", transaction_receipt.transaction_type.unwrap());
+    let blob_gas_used =
+        other_u256(&transaction_receipt.other, "blobGasUsed").map(U256Numeric::from);
+    let blob_gas_price =
+        other_u256(&transaction_receipt.other, "blobGasPrice").map(U256Numeric::from);
 
     // Build the SQL query
     let query = r#"
         INSERT INTO transactions_receipts ("transactionHash", "transactionIndex", "blockHash", "from",
                                             "to", "blockNumber", "cumulativeGasUsed", "gasUsed",
                                             "contractAddress", "logs", "logsBloom", "status",
-                                            "effectiveGasPrice", "type")
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13 ,$14)
+                                            "effectiveGasPrice", "type", "blobGasUsed", "blobGasPrice")
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13 ,$14, $15, $16)
         ON CONFLICT ("transactionHash") DO NOTHING;
     "#;
     // Prepare the statement
@@ -192,7 +546,7 @@ pub async fn insert_transaction_receipt(
         .await
         .expect("Failed to prepare statement");
     // Prepare the parameter values
-    let params: [&(dyn ToSql + Sync); 14] = [
+    let params: [&(dyn ToSql + Sync); 16] = [
         &transaction_hash,
         &transaction_index,
         &block_hash,
@@ -207,6 +561,8 @@ pub async fn insert_transaction_receipt(
         &status,
         &effective_gas_price,
         &transaction_type,
+        &blob_gas_used,
+        &blob_gas_price,
     ];
 
     // Execute the query with parameters
@@ -1,20 +1,22 @@
 // Module that handle block indexing
 // blocks/mod.rs
-use crate::{blockscout, db, rpc};
+use crate::{blockscout, db, db::DbTlsConnector, retry, rpc};
 use bb8::Pool;
 use bb8_postgres::PostgresConnectionManager;
 use ethers::prelude::*;
+use futures::stream::FuturesOrdered;
+use futures::StreamExt;
 use log::{error as log_error, info, warn};
+use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::Semaphore;
-use tokio_postgres::NoTls;
+use tokio::sync::{mpsc, watch};
 
 pub struct Indexer {
     ws_clients: Vec<Arc<Provider<Ws>>>,
-    db_pools: Vec<Pool<PostgresConnectionManager<NoTls>>>,
+    db_pools: Vec<Pool<PostgresConnectionManager<DbTlsConnector>>>,
 }
 
 impl Indexer {
@@ -40,27 +42,61 @@ impl Indexer {
             db_pools.push(db_pool);
         }
 
+        // Publish pool saturation for the first pool (the one `db_pool()` hands out to callers
+        // like `index_serve`); the others are extra write connections, not a separate resource a
+        // scraper needs to watch independently.
+        tokio::spawn(crate::metrics::track_db_pool(
+            db_pools[0].clone(),
+            db::configured_pool_max_size(),
+        ));
+
         // Connect to the WS RPC endpoint and database
         Indexer {
             ws_clients,
             db_pools,
         }
     }
-    pub async fn run(&self) -> Result<(), Box<dyn std::error::Error>> {
+    /// Hand out a clone of the first database pool, the same one `run`/`run_live`/
+    /// `run_last_blocks` use for their own inserts, so a caller like `index_serve` can read from
+    /// the same database without opening a second connection pool.
+    pub fn db_pool(&self) -> Pool<PostgresConnectionManager<DbTlsConnector>> {
+        self.db_pools.get(0).unwrap().clone()
+    }
+
+    pub async fn run(
+        &self,
+        shutdown: watch::Receiver<bool>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Init database
         // TODO: maybe move this
         if let Err(e) = db::init_db(self.db_pools.get(0).unwrap().clone()).await {
             log_error!("Error initializing the database: {}", e);
         }
         let last_block = get_latest_block(self.ws_clients.get(0).unwrap().clone()).await?;
-        // Use some env variables to set the start and end block
-        // By default we will index all the blocks
-        let start_block = U64::from(
-            env::var("START_BLOCK")
-                .unwrap_or_else(|_| "0".to_string())
-                .parse::<u64>()
-                .unwrap_or(0),
-        );
+        // Resume from the persisted cursor (see `db::state`) when one is on record; otherwise
+        // fall back to `START_BLOCK` as before, e.g. on a fresh database.
+        let start_block = match db::get_cursor(self.db_pools.get(0).unwrap().clone()).await {
+            Ok(Some(cursor)) => {
+                let resume_from = cursor + U64::from(1);
+                warn!("Resuming from persisted cursor: block {}", resume_from);
+                resume_from
+            }
+            Ok(None) => U64::from(
+                env::var("START_BLOCK")
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse::<u64>()
+                    .unwrap_or(0),
+            ),
+            Err(e) => {
+                log_error!("Error reading indexing cursor, falling back to START_BLOCK: {}", e);
+                U64::from(
+                    env::var("START_BLOCK")
+                        .unwrap_or_else(|_| "0".to_string())
+                        .parse::<u64>()
+                        .unwrap_or(0),
+                )
+            }
+        };
         let end_block = U64::from(
             env::var("END_BLOCK")
                 .unwrap_or_else(|_| "-1".to_string())
@@ -76,16 +112,125 @@ impl Indexer {
             end_block,
             self.ws_clients.clone(),
             self.db_pools.clone(),
+            shutdown.clone(),
         )
         .await
         {
-            Ok(_) => info!("Indexing complete!",),
+            Ok(_) if *shutdown.borrow() => {
+                warn!("Indexing interrupted before block {}; cursor left unchanged", end_block);
+            }
+            Ok(_) => {
+                info!("Indexing complete!");
+                if let Err(e) =
+                    db::set_cursor(end_block, self.db_pools.get(0).unwrap().clone()).await
+                {
+                    log_error!("Error persisting indexing cursor: {}", e);
+                }
+            }
             Err(e) => log_error!("Error indexing blocks: {}", e),
         }
         info!("Done!");
         Ok(())
     }
 
+    /// Scan the `blocks` table for gaps (block numbers missing between its current min and max)
+    /// and re-index only those, through the same per-block `index_block` path `run_live` uses.
+    /// This is meant to be run on demand to repair a database left with holes, e.g. from a past
+    /// crash that lost an in-memory batch before `run`'s cursor could be persisted, rather than
+    /// as part of the normal indexing loop.
+    pub async fn run_backfill(
+        &self,
+        shutdown: watch::Receiver<bool>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Err(e) = db::init_db(self.db_pools.get(0).unwrap().clone()).await {
+            log_error!("Error initializing the database: {}", e);
+        }
+
+        let db_pool = self.db_pools.get(0).unwrap().clone();
+        let (min_block, max_block) = match db::get_block_range(db_pool.clone()).await? {
+            Some(range) => range,
+            None => {
+                info!("No blocks indexed yet, nothing to backfill");
+                return Ok(());
+            }
+        };
+
+        let gaps = db::find_gaps(min_block, max_block, db_pool.clone()).await?;
+        if gaps.is_empty() {
+            info!("No gaps found between block {} and {}", min_block, max_block);
+            return Ok(());
+        }
+        warn!(
+            "Found {} gap range(s) between block {} and {}, backfilling...",
+            gaps.len(),
+            min_block,
+            max_block
+        );
+
+        let ws_client = self.ws_clients.get(0).unwrap().clone();
+        'gaps: for (gap_start, gap_end) in gaps {
+            for block_number in gap_start.as_u64()..=gap_end.as_u64() {
+                if *shutdown.borrow() {
+                    warn!("Backfill interrupted at block {}", block_number);
+                    break 'gaps;
+                }
+                if let Err(e) =
+                    index_block(U64::from(block_number), ws_client.clone(), db_pool.clone()).await
+                {
+                    log_error!("Error backfilling block {}: {:?}", block_number, e);
+                }
+            }
+        }
+
+        info!("Backfill complete!");
+        Ok(())
+    }
+
+    /// Drain the `failed_blocks` dead-letter queue (see `db::failed_blocks`) back through
+    /// `index_block`, one at a time. `index_block`'s own `retry::call_with_retry` calls have
+    /// already been exhausted by the time a block lands here, so this just gives it a second
+    /// chance once the endpoint or database has had time to recover; a block that fails again
+    /// re-records itself and simply stays in the queue for the next run.
+    pub async fn run_retry_failed(
+        &self,
+        shutdown: watch::Receiver<bool>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if let Err(e) = db::init_db(self.db_pools.get(0).unwrap().clone()).await {
+            log_error!("Error initializing the database: {}", e);
+        }
+
+        let db_pool = self.db_pools.get(0).unwrap().clone();
+        let failed_blocks = db::list_failed_blocks(db_pool.clone()).await?;
+        if failed_blocks.is_empty() {
+            info!("No failed blocks to retry");
+            return Ok(());
+        }
+        warn!("Retrying {} failed block(s)", failed_blocks.len());
+
+        let ws_client = self.ws_clients.get(0).unwrap().clone();
+        for block_number in failed_blocks {
+            if *shutdown.borrow() {
+                warn!("Retry-failed interrupted before block {}", block_number);
+                break;
+            }
+            match index_block(block_number, ws_client.clone(), db_pool.clone()).await {
+                Ok(()) => {
+                    if let Err(e) = db::clear_failed_block(block_number, db_pool.clone()).await {
+                        log_error!(
+                            "Error clearing block {} from the failed-blocks queue: {:?}",
+                            block_number,
+                            e
+                        );
+                    }
+                }
+                Err(e) => log_error!("Block {} failed again: {}", block_number, e),
+            }
+        }
+
+        info!("Retry-failed complete!");
+        Ok(())
+    }
+
     pub async fn run_live(&self) -> Result<(), Box<dyn std::error::Error>> {
         // Init database
         // TODO: maybe move this
@@ -130,6 +275,7 @@ impl Indexer {
     pub async fn run_last_blocks(
         &self,
         number_of_blocks: u64,
+        shutdown: watch::Receiver<bool>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         // Init database
         // TODO: maybe move this
@@ -147,6 +293,7 @@ impl Indexer {
             last_block,
             self.ws_clients.clone(),
             self.db_pools.clone(),
+            shutdown,
         )
         .await
         {
@@ -169,81 +316,189 @@ async fn get_latest_block(ws_client: Arc<Provider<Ws>>) -> Result<U64, Box<dyn E
     }
 }
 
-/// Index the blocks
-/// We will index the blocks in parallel in batches of `BATCH_SIZE` blocks.
-/// The batch size can be configured with the environment variable `BATCH_SIZE`.
+/// Index the blocks as a producer/consumer pipeline.
 ///
-/// A block is indexed by calling the `index_block` function.
-/// A block contains a list of transactions. Each transaction is indexed by
-/// calling the `index_transaction` function.
+/// A fetch stage keeps up to `FETCH_CONCURRENCY` `get_block`/`get_transaction` calls in flight at
+/// once via a bounded `FuturesOrdered`, preserving block order, and pushes each fully-fetched
+/// `(block, transactions)` pair into an `mpsc` channel of capacity `PIPELINE_CHANNEL_CAPACITY`. A
+/// dedicated writer task drains that channel, accumulating up to `BATCH_SIZE` blocks before
+/// flushing a bulk `COPY` (see `db::insert_blocks_bulk`/`db::insert_transactions_bulk`) and then
+/// running the per-transaction indexing.
 ///
+/// Splitting fetch from write this way means a slow DB flush no longer stalls in-flight RPC
+/// calls (and vice versa): the channel bound is the only coupling between the two, and it also
+/// caps how many fetched-but-not-yet-written blocks can pile up in memory during a backfill.
+///
+/// `shutdown` is watched by the fetch stage: once it flips to `true` (wired to CTRL+C in
+/// `main.rs`), no further blocks are scheduled, but blocks already in flight are left to finish
+/// and drain through the channel, so the writer flushes every block it received (the last batch
+/// possibly short of `BATCH_SIZE`) instead of losing whatever was left sitting in the buffer.
 async fn index_blocks(
     start_block: U64,
     end_block: U64,
     ws_clients: Vec<Arc<Provider<Ws>>>,
-    db_pools: Vec<Pool<PostgresConnectionManager<NoTls>>>,
+    db_pools: Vec<Pool<PostgresConnectionManager<DbTlsConnector>>>,
+    shutdown: watch::Receiver<bool>,
 ) -> Result<(), String> {
-    let max_concurrency: U64 = env::var("MAX_CONCURRENCY")
+    let fetch_concurrency: usize = env::var("FETCH_CONCURRENCY")
         .unwrap_or_else(|_| "100".to_string())
         .parse()
-        .unwrap_or(U64::from(100));
-    let semaphore = Arc::new(Semaphore::new(max_concurrency.as_u32() as usize));
-    let mut batch_start = start_block;
-    let mut batch_end = batch_start + max_concurrency;
+        .unwrap_or(100);
+    let channel_capacity: usize = env::var("PIPELINE_CHANNEL_CAPACITY")
+        .unwrap_or_else(|_| "200".to_string())
+        .parse()
+        .unwrap_or(200);
+    let batch_size: usize = env::var("BATCH_SIZE")
+        .unwrap_or_else(|_| "50".to_string())
+        .parse()
+        .unwrap_or(50);
 
-    let total_blocks = end_block.as_u64() - start_block.as_u64();
-    let mut blocks_processed = 0;
-    let mut blocks_processed_total = 0;
-    let mut start_time: Instant = Instant::now();
+    let (tx, rx) = mpsc::channel(channel_capacity);
 
-    let ws_client_count = ws_clients.len();
-    let db_pool_count = db_pools.len();
+    let fetch_ws_clients = ws_clients.clone();
+    let fetch_handle = tokio::spawn(async move {
+        run_fetch_pipeline(
+            start_block,
+            end_block,
+            fetch_ws_clients,
+            fetch_concurrency,
+            tx,
+            shutdown,
+        )
+        .await;
+    });
 
-    while batch_end <= end_block {
-        // println!("Indexing blocks {} to {}", batch_start, batch_end);
+    let write_handle = tokio::spawn(async move {
+        run_write_pipeline(start_block, end_block, ws_clients, db_pools, batch_size, rx).await;
+    });
 
-        let mut tasks = vec![];
+    if let Err(e) = fetch_handle.await {
+        log_error!("Error joining fetch pipeline task: {}", e);
+    }
+    if let Err(e) = write_handle.await {
+        log_error!("Error joining write pipeline task: {}", e);
+    }
+
+    Ok(())
+}
+
+/// Fetch stage of the pipeline: pulls `block_numbers` from `start_block` to `end_block`
+/// concurrently (bounded by `fetch_concurrency`) while preserving order via `FuturesOrdered`,
+/// then sends each fetched `(block, transactions)` pair to the writer over `tx`. Stops early if
+/// the writer side of the channel has been dropped, or if `shutdown` flips to `true`, in which
+/// case blocks already in flight are still drained and sent before returning.
+async fn run_fetch_pipeline(
+    start_block: U64,
+    end_block: U64,
+    ws_clients: Vec<Arc<Provider<Ws>>>,
+    fetch_concurrency: usize,
+    tx: mpsc::Sender<(Block<H256>, Vec<Transaction>)>,
+    shutdown: watch::Receiver<bool>,
+) {
+    let ws_client_count = ws_clients.len();
+    let mut next_block = start_block.as_u64();
+    let mut in_flight = FuturesOrdered::new();
+
+    while next_block <= end_block.as_u64()
+        && in_flight.len() < fetch_concurrency
+        && !*shutdown.borrow()
+    {
+        let ws_client = Arc::clone(&ws_clients[next_block as usize % ws_client_count]);
+        in_flight.push_back(fetch_block_with_transactions(next_block, ws_client));
+        next_block += 1;
+    }
+    crate::metrics::IN_FLIGHT_FETCHES.set(in_flight.len() as i64);
 
-        for block_number in batch_start.as_u64()..batch_end.as_u64() {
-            // Acquire a permit before spawning a new task
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
+    while let Some(result) = in_flight.next().await {
+        if next_block <= end_block.as_u64() && !*shutdown.borrow() {
+            let ws_client = Arc::clone(&ws_clients[next_block as usize % ws_client_count]);
+            in_flight.push_back(fetch_block_with_transactions(next_block, ws_client));
+            next_block += 1;
+        }
+        crate::metrics::IN_FLIGHT_FETCHES.set(in_flight.len() as i64);
 
-            //skip if block_number is > end_block
-            if block_number > end_block.as_u64() {
-                continue;
+        if let Some(fetched) = result {
+            if tx.send(fetched).await.is_err() {
+                // Writer task is gone; no point fetching further blocks.
+                break;
             }
-            let ws_client_index = block_number as usize % ws_client_count;
-            let db_pool_index = block_number as usize % db_pool_count;
+        }
+    }
+    crate::metrics::IN_FLIGHT_FETCHES.set(0);
+}
 
-            let thd_ws_client = Arc::clone(&ws_clients.get(ws_client_index).unwrap());
-            let thd_db_pool = db_pools.get(db_pool_index).unwrap().clone(); // Clone the connection pool for each thread
-            let thd_block_number = block_number.clone();
+/// Fetch a single block and every `Transaction` it references.
+async fn fetch_block_with_transactions(
+    block_number: u64,
+    ws_client: Arc<Provider<Ws>>,
+) -> Option<(Block<H256>, Vec<Transaction>)> {
+    let block = match ws_client.get_block(U64::from(block_number)).await {
+        Ok(Some(block)) => block,
+        Ok(None) => return None,
+        Err(e) => {
+            log_error!("Error fetching block {}: {}", block_number, e);
+            return None;
+        }
+    };
 
-            tasks.push(tokio::spawn(async move {
-                let _permit = permit; // Ensure permit is held until task is done.
-                index_block(U64::from(thd_block_number), thd_ws_client, thd_db_pool).await
-            }));
+    let mut transactions = Vec::with_capacity(block.transactions.len());
+    for transaction_hash in &block.transactions {
+        match ws_client.get_transaction(*transaction_hash).await {
+            Ok(Some(transaction)) => transactions.push(transaction),
+            _ => log_error!(
+                "Error retrieving transaction {:#x} for block {}",
+                transaction_hash,
+                block_number
+            ),
         }
+    }
 
-        for task in tasks {
-            if let Err(e) = task.await {
-                log_error!("Error indexing blocks: {}", e);
+    Some((block, transactions))
+}
+
+/// Write stage of the pipeline: drains `rx`, accumulating up to `batch_size` fetched blocks
+/// before flushing them as a single bulk `COPY` for blocks and transactions, then running the
+/// per-transaction indexing for the flushed batch.
+async fn run_write_pipeline(
+    start_block: U64,
+    end_block: U64,
+    ws_clients: Vec<Arc<Provider<Ws>>>,
+    db_pools: Vec<Pool<PostgresConnectionManager<DbTlsConnector>>>,
+    batch_size: usize,
+    mut rx: mpsc::Receiver<(Block<H256>, Vec<Transaction>)>,
+) {
+    let ws_client_count = ws_clients.len();
+    let total_blocks = end_block.as_u64().saturating_sub(start_block.as_u64());
+    let mut blocks_processed = 0u64;
+    let mut blocks_processed_total = 0u64;
+    let mut start_time = Instant::now();
+
+    let mut buffer: Vec<(Block<H256>, Vec<Transaction>)> = Vec::with_capacity(batch_size);
+    while let Some(fetched) = rx.recv().await {
+        buffer.push(fetched);
+        while buffer.len() < batch_size {
+            match rx.try_recv() {
+                Ok(fetched) => buffer.push(fetched),
+                Err(_) => break,
             }
         }
 
-        batch_start += max_concurrency;
-        batch_end += max_concurrency;
+        let flushed = buffer.len() as u64;
+        let last_block_in_batch = buffer.last().and_then(|(block, _)| block.number);
+        flush_batch(&mut buffer, &db_pools[0], &ws_clients, ws_client_count).await;
+
+        if let Some(last_block_number) = last_block_in_batch {
+            crate::metrics::HEAD_LAG
+                .set(end_block.as_u64().saturating_sub(last_block_number.as_u64()) as i64);
+        }
 
-        // Calculate stats and log it every 10 seconds
-        blocks_processed += max_concurrency.as_u64();
-        blocks_processed_total += max_concurrency.as_u64();
+        blocks_processed += flushed;
+        blocks_processed_total += flushed;
         let elapsed_time = start_time.elapsed();
         if elapsed_time >= Duration::new(5, 0) {
-            let progress = blocks_processed_total as f64 / total_blocks as f64 * 100.0;
-
-            // Calculate estimated remaining time
+            let progress = blocks_processed_total as f64 / total_blocks.max(1) as f64 * 100.0;
             let elapsed_seconds = elapsed_time.as_secs_f64();
-            let remaining_blocks = total_blocks - blocks_processed_total;
+            let remaining_blocks = total_blocks.saturating_sub(blocks_processed_total);
             let estimated_remaining_time_secs = if blocks_processed > 0 {
                 (remaining_blocks as f64 / blocks_processed as f64) * elapsed_seconds
             } else {
@@ -265,87 +520,295 @@ async fn index_blocks(
             blocks_processed = 0;
         }
     }
+}
 
-    // Index the remaining blocks
-    if batch_start < end_block {
-        let mut tasks = vec![];
+/// Flush a buffered batch of `(block, transactions)` pairs: bulk-insert the blocks, bulk-insert
+/// every transaction in the batch, fetch and bulk-insert every one of their receipts and logs,
+/// then run the per-transaction indexing for each block using the already-fetched receipts.
+///
+/// Logs are only bulk-inserted here when `BATCH_INSERT` (default `true`) isn't set to `"false"`;
+/// otherwise the original one-`insert_log`-per-log path in `index_transaction_details` runs for
+/// them. `run_live` streams through `index_block` directly and always uses that per-row path,
+/// except for the range `index_block` re-indexes through `index_blocks` (and so through
+/// `flush_batch`) after a reorg.
+async fn flush_batch(
+    batch: &mut Vec<(Block<H256>, Vec<Transaction>)>,
+    db_pool: &Pool<PostgresConnectionManager<DbTlsConnector>>,
+    ws_clients: &[Arc<Provider<Ws>>],
+    ws_client_count: usize,
+) {
+    if batch.is_empty() {
+        return;
+    }
 
-        for block_number in batch_start.as_u64()..batch_end.as_u64() {
-            //skip if block_number is > end_block
-            if block_number > end_block.as_u64() {
-                continue;
-            }
+    let flush_start_time = Instant::now();
+    let blocks: Vec<Block<H256>> = batch.iter().map(|(block, _)| block.clone()).collect();
+    let blocks_in_batch = blocks.len() as u64;
+    match db::insert_blocks_bulk(blocks, db_pool.clone()).await {
+        Ok(()) => crate::metrics::BLOCKS_INDEXED.inc_by(blocks_in_batch),
+        Err(e) => {
+            log_error!("Error bulk-inserting blocks: {}", e);
+            crate::metrics::DB_INSERT_ERRORS.inc();
+        }
+    }
 
-            let ws_client_index = block_number as usize % ws_client_count;
-            let db_pool_index = block_number as usize % db_pool_count;
+    let all_transactions: Vec<Transaction> = batch
+        .iter()
+        .flat_map(|(_, transactions)| transactions.clone())
+        .collect();
+    let transactions_in_batch = all_transactions.len() as u64;
+    match db::insert_transactions_bulk(all_transactions.clone(), db_pool.clone()).await {
+        Ok(()) => crate::metrics::TRANSACTIONS_INDEXED.inc_by(transactions_in_batch),
+        Err(e) => {
+            log_error!("Error bulk-inserting transactions: {:?}", e);
+            crate::metrics::DB_INSERT_ERRORS.inc();
+        }
+    }
 
-            let thd_ws_client = Arc::clone(&ws_clients.get(ws_client_index).unwrap());
-            let thd_db_pool = db_pools.get(db_pool_index).unwrap().clone(); // Clone the connection pool for each thread
-            let thd_block_number = block_number.clone();
+    // Fetch every transaction's receipt up front (same bounded-concurrency, order-preserving
+    // shape as the block fetch stage) and bulk-insert them in one `COPY`, so the per-transaction
+    // indexing below can reuse the already-fetched receipt instead of doing its own round trip.
+    let mut receipt_fetches = FuturesOrdered::new();
+    for transaction in &all_transactions {
+        let ws_client = Arc::clone(
+            &ws_clients[transaction.block_number.unwrap().as_u64() as usize % ws_client_count],
+        );
+        let transaction_hash = transaction.hash;
+        receipt_fetches.push_back(async move {
+            match ws_client.get_transaction_receipt(transaction_hash).await {
+                Ok(Some(receipt)) => Some(receipt),
+                _ => {
+                    log_error!("Error fetching transaction receipt {}", transaction_hash);
+                    None
+                }
+            }
+        });
+    }
+    let mut receipts_by_hash: HashMap<H256, TransactionReceipt> = HashMap::new();
+    while let Some(receipt) = receipt_fetches.next().await {
+        if let Some(receipt) = receipt {
+            receipts_by_hash.insert(receipt.transaction_hash, receipt);
+        }
+    }
+    let receipts_in_batch = receipts_by_hash.len() as u64;
+    let receipts: Vec<TransactionReceipt> = receipts_by_hash.values().cloned().collect();
+    if let Err(e) = db::insert_transaction_receipts_bulk(receipts, db_pool.clone()).await {
+        log_error!("Error bulk-inserting transaction receipts: {:?}", e);
+        crate::metrics::DB_INSERT_ERRORS.inc_by(receipts_in_batch);
+    }
 
-            tasks.push(tokio::spawn(async move {
-                index_block(U64::from(thd_block_number), thd_ws_client, thd_db_pool).await
-            }));
+    // `BATCH_INSERT` only governs logs (the blocks, transactions and receipts above are always
+    // bulk-inserted): when enabled (the default), bulk
+    // `COPY` every log across the whole batch's receipts up front and skip the per-row inserts
+    // `index_transaction_details` would otherwise do for each one; when disabled, fall back to
+    // that per-row path exactly as `run_live` always has.
+    let batch_insert_logs = env::var("BATCH_INSERT")
+        .map(|value| value != "false")
+        .unwrap_or(true);
+    let mut logs_already_inserted = false;
+    if batch_insert_logs {
+        let all_logs: Vec<Log> = receipts_by_hash
+            .values()
+            .flat_map(|receipt| receipt.logs.clone())
+            .collect();
+        let logs_in_batch = all_logs.len() as u64;
+        match db::insert_logs_bulk(all_logs, db_pool.clone()).await {
+            Ok(()) => {
+                crate::metrics::LOGS_INDEXED.inc_by(logs_in_batch);
+                logs_already_inserted = true;
+            }
+            Err(e) => {
+                log_error!("Error bulk-inserting logs: {:?}", e);
+                crate::metrics::DB_INSERT_ERRORS.inc();
+            }
         }
+    }
 
-        for task in tasks {
-            if let Err(e) = task.await {
-                log_error!("Error indexing blocks: {}", e);
+    let receipts_by_hash = Arc::new(receipts_by_hash);
+
+    let mut tx_tasks = vec![];
+    for (block, transactions) in batch.drain(..) {
+        let block_number = block.number.unwrap().as_u64();
+        let ws_client = Arc::clone(&ws_clients[block_number as usize % ws_client_count]);
+        let thd_db_pool = db_pool.clone();
+        let thd_receipts = Arc::clone(&receipts_by_hash);
+
+        tx_tasks.push(tokio::spawn(async move {
+            for transaction in transactions {
+                let receipt = thd_receipts.get(&transaction.hash).cloned();
+                if let Err(e) = index_transaction_details(
+                    transaction,
+                    ws_client.clone(),
+                    thd_db_pool.clone(),
+                    receipt,
+                    logs_already_inserted,
+                )
+                .await
+                {
+                    log_error!("Error indexing transaction details: {:?}", e);
+                }
             }
+        }));
+    }
+    for task in tx_tasks {
+        if let Err(e) = task.await {
+            log_error!("Error indexing block transactions: {}", e);
         }
     }
 
-    Ok(())
+    // This path doesn't time individual blocks the way `index_block` does, so record the
+    // batch's average per-block latency instead of leaving the bulk backfill path unobserved.
+    let per_block_latency = flush_start_time.elapsed().as_secs_f64() / blocks_in_batch.max(1) as f64;
+    crate::metrics::BLOCK_INDEX_LATENCY_SECONDS.observe(per_block_latency);
 }
 
 /// Index a block
 /// A block contains a list of transactions. Each transaction is indexed by
 /// calling the `index_transaction` function.
+///
+/// RPC/DB calls that fail outright (after `retry::call_with_retry` has exhausted its attempts
+/// on transient errors) are recorded in the `failed_blocks` dead-letter queue via
+/// `record_failed_block` before the error is returned, so `Indexer::run_retry_failed` can sweep
+/// back over this block later instead of it being lost to a log line.
 async fn index_block(
     block_number: U64,
     ws_client: Arc<Provider<Ws>>,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Result<(), String> {
+    let start_time = Instant::now();
+    let result = index_block_inner(block_number, ws_client, db_pool).await;
+    crate::metrics::BLOCK_INDEX_LATENCY_SECONDS.observe(start_time.elapsed().as_secs_f64());
+    result
+}
+
+async fn index_block_inner(
+    block_number: U64,
+    ws_client: Arc<Provider<Ws>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), String> {
-    match ws_client.get_block(block_number).await {
-        Ok(Some(block)) => {
-            // Index block
-            if let Err(e) = db::insert_block(block.clone(), db_pool.to_owned()).await {
+    let fetch_description = format!("fetching block {}", block_number);
+    let block = match retry::call_with_retry(&fetch_description, || ws_client.get_block(block_number)).await
+    {
+        Ok(Some(block)) => block,
+        Ok(None) => {
+            let error_message = format!("Error retrieving block {}: block not found", block_number);
+            log_error!("{}", error_message);
+            record_failed_block(block_number, &error_message, db_pool.clone()).await;
+            return Err(error_message);
+        }
+        Err(e) => {
+            let error_message = format!("Error retrieving block {}: {}", block_number, e);
+            log_error!("{}", error_message);
+            record_failed_block(block_number, &error_message, db_pool.clone()).await;
+            return Err(error_message);
+        }
+    };
+
+    // Index block. `insert_block` detects a reorg against the stored chain itself (see
+    // `db::blocks::detect_and_handle_reorg`) and rolls back the divergent range before
+    // writing this block's row, since `run_live` follows the chain head rather than
+    // doing a one-shot historical backfill.
+    let insert_description = format!("inserting block {}", block_number);
+    let divergent_number = match retry::call_with_retry(&insert_description, || {
+        db::insert_block(block.clone(), ws_client.clone(), db_pool.to_owned())
+    })
+    .await
+    {
+        Ok(divergent_number) => divergent_number,
+        Err(e) => {
+            let error_message = format!(
+                "Error inserting block #{} into database: {:?}",
+                block_number, e
+            );
+            log_error!("{}", error_message);
+            record_failed_block(block_number, &error_message, db_pool.clone()).await;
+            return Err(error_message); // Return the error message
+        }
+    };
+
+    if let Some(divergent_number) = divergent_number {
+        if divergent_number < block_number {
+            // The rollback just deleted every stored block from `divergent_number`
+            // onward; `run_live` only ever streams the new head, so nothing else will
+            // backfill the canonical blocks in between unless we do it here.
+            let reindex_end = U64::from(block_number.as_u64() - 1);
+            warn!(
+                "Re-indexing blocks {} to {} on the canonical chain after reorg",
+                divergent_number, reindex_end
+            );
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            if let Err(e) = index_blocks(
+                divergent_number,
+                reindex_end,
+                vec![ws_client.clone()],
+                vec![db_pool.clone()],
+                shutdown_rx,
+            )
+            .await
+            {
                 let error_message = format!(
-                    "Error inserting block #{} into database: {:?}",
-                    block_number, e
+                    "Error re-indexing blocks {}-{} after reorg: {}",
+                    divergent_number, reindex_end, e
                 );
                 log_error!("{}", error_message);
-                return Err(error_message); // Return the error message
+                record_failed_block(block_number, &error_message, db_pool.clone()).await;
+                return Err(error_message);
             }
+        }
+    }
 
-            // Index transactions only after inserting the block
-            for transaction_hash in block.transactions {
-                let ws_client = Arc::clone(&ws_client);
-                let thd_db_pool = db_pool.clone(); // Clone the connection pool for each thread
-
-                if let Err(e) = index_transaction(transaction_hash, ws_client, &thd_db_pool).await {
-                    let error_message = format!(
-                        "Error indexing transaction #{}: {:?}",
-                        format!("0x{:x}", transaction_hash),
-                        e
-                    );
-                    log_error!("{}", error_message);
-                }
-            }
+    // Index transactions only after inserting the block
+    let mut transaction_error = None;
+    for transaction_hash in block.transactions {
+        let ws_client = Arc::clone(&ws_client);
+        let thd_db_pool = db_pool.clone(); // Clone the connection pool for each thread
+
+        if let Err(e) = index_transaction(transaction_hash, ws_client, &thd_db_pool).await {
+            let error_message = format!(
+                "Error indexing transaction #{}: {:?}",
+                format!("0x{:x}", transaction_hash),
+                e
+            );
+            log_error!("{}", error_message);
+            transaction_error = Some(error_message);
         }
-        _ => log_error!("Error retrieving block {}", block_number),
+    }
+
+    if let Some(error_message) = transaction_error {
+        record_failed_block(block_number, &error_message, db_pool.clone()).await;
+        return Err(error_message);
     }
 
     Ok(())
 }
 
+/// Record `block_number` as failed in the `failed_blocks` dead-letter queue (see
+/// `db::failed_blocks`), logging rather than propagating if the write itself fails — losing a
+/// dead-letter entry shouldn't mask the original indexing error that's already being returned.
+async fn record_failed_block(
+    block_number: U64,
+    error: &str,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) {
+    crate::metrics::FAILED_BLOCKS.inc();
+    if let Err(e) = db::record_failed_block(block_number, error, db_pool).await {
+        log_error!(
+            "Error recording block {} in the failed-blocks queue: {:?}",
+            block_number,
+            e
+        );
+    }
+}
+
 /// Index a transaction
 async fn index_transaction(
     transaction_hash: TxHash,
     ws_client: Arc<Provider<Ws>>,
-    db_pool: &Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: &Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), String> {
-    match ws_client.get_transaction(transaction_hash).await {
+    let fetch_description = format!("fetching transaction {:#x}", transaction_hash);
+    match retry::call_with_retry(&fetch_description, || ws_client.get_transaction(transaction_hash)).await
+    {
         Ok(Some(transaction)) => {
             // Index transaction
             if let Err(e) = db::insert_transaction(transaction.clone(), db_pool.clone()).await {
@@ -353,96 +816,144 @@ async fn index_transaction(
                 log_error!("{}", error_message);
                 return Err(error_message); // Return the error message
             }
-            // Index the from address
-            if let Err(e) = index_address(
-                transaction.from,
-                transaction.block_number.unwrap(),
-                ws_client.clone(),
-                db_pool.clone(),
-            )
-            .await
-            {
-                let error_message = format!("Error indexing address: {:?}", e);
-                log_error!("{}", error_message);
-                return Err(error_message); // Return the error message
+            index_transaction_details(transaction, ws_client, db_pool.clone(), None, false).await
+        }
+        _ => {
+            let error_message = format!("Error fetching transaction {:#x}", transaction_hash);
+            log_error!("{}", error_message);
+            Err(error_message)
+        }
+    }
+}
+
+/// Index everything that hangs off an already-inserted transaction: the from/to/contract
+/// addresses, the receipt, the smart contract (if any), and the receipt's logs.
+///
+/// `receipt` lets a caller that already fetched (and bulk-inserted) the receipt hand it over
+/// directly, e.g. `flush_batch` during a backfill; passing `None` makes this fetch and insert the
+/// receipt itself, as it always did before batching existed.
+///
+/// `logs_already_inserted` is `true` only when `flush_batch` already bulk-`COPY`'d this receipt's
+/// logs for the whole batch (see `db::insert_logs_bulk`); in that case the per-row loop below is
+/// skipped. `run_live` (via `index_transaction`) always passes `false`, since it streams blocks
+/// one at a time and never builds a batch to bulk-insert logs from.
+async fn index_transaction_details(
+    transaction: Transaction,
+    ws_client: Arc<Provider<Ws>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+    receipt: Option<TransactionReceipt>,
+    logs_already_inserted: bool,
+) -> Result<(), String> {
+    let transaction_hash = transaction.hash;
+    // Index the from address
+    if let Err(e) = index_address(
+        transaction.from,
+        transaction.block_number.unwrap(),
+        ws_client.clone(),
+        db_pool.clone(),
+    )
+    .await
+    {
+        let error_message = format!("Error indexing address: {:?}", e);
+        log_error!("{}", error_message);
+        return Err(error_message); // Return the error message
+    }
+    // Index the to address, if address is not zero
+    if transaction.to.unwrap_or(Address::zero()) != Address::zero() {
+        if let Err(e) = index_address(
+            transaction.to.unwrap(),
+            transaction.block_number.unwrap(),
+            ws_client.clone(),
+            db_pool.clone(),
+        )
+        .await
+        {
+            let error_message = format!("Error indexing address: {:?}", e);
+            log_error!("{}", error_message);
+            return Err(error_message); // Return the error message
+        }
+    }
+    // Get the transaction receipt, unless the caller already fetched (and bulk-inserted) it.
+    let transaction_receipt = match receipt {
+        Some(transaction_receipt) => Some(transaction_receipt),
+        None => match ws_client.get_transaction_receipt(transaction_hash).await {
+            Ok(Some(transaction_receipt)) => {
+                if let Err(e) =
+                    db::insert_transaction_receipt(transaction_receipt.clone(), db_pool.clone())
+                        .await
+                {
+                    let error_message =
+                        format!("Error inserting transaction receipt into database: {:?}", e);
+                    log_error!("{}", error_message);
+                    return Err(error_message); // Return the error message
+                }
+                Some(transaction_receipt)
             }
-            // Index the to address, if address is not zero
-            if transaction.to.unwrap_or(Address::zero()) != Address::zero() {
+            _ => None,
+        },
+    };
+
+    match transaction_receipt {
+        Some(transaction_receipt) => {
+            // Index the contract
+            if let Some(contract_address) = transaction_receipt.contract_address {
+                // Index the contract address
                 if let Err(e) = index_address(
-                    transaction.to.unwrap(),
+                    contract_address,
                     transaction.block_number.unwrap(),
                     ws_client.clone(),
                     db_pool.clone(),
                 )
                 .await
                 {
-                    let error_message = format!("Error indexing address: {:?}", e);
+                    let error_message = format!("Error indexing contract address: {:?}", e);
+                    log_error!("{}", error_message);
+                    return Err(error_message); // Return the error message
+                }
+                // Index the smart contract (code and verified source code)
+                if let Err(e) = index_smart_contract(
+                    transaction_receipt.clone(),
+                    ws_client.clone(),
+                    db_pool.clone(),
+                )
+                .await
+                {
+                    let error_message = format!("Error indexing smart contract code: {:?}", e);
                     log_error!("{}", error_message);
                     return Err(error_message); // Return the error message
                 }
             }
-            // Get the transaction receipt
-            match ws_client.get_transaction_receipt(transaction_hash).await {
-                Ok(Some(transaction_receipt)) => {
-                    // Index transaction receipt
-                    if let Err(e) =
-                        db::insert_transaction_receipt(transaction_receipt.clone(), db_pool.clone())
-                            .await
-                    {
-                        let error_message =
-                            format!("Error inserting transaction receipt into database: {:?}", e);
+            // Detect and store ERC-20/ERC-721 Transfer and ERC-1155 TransferSingle/TransferBatch
+            // events directly off the receipt's raw
+            // logs, independent of whether the contract has a verified ABI on file.
+            if let Err(e) = db::index_transfers(&transaction_receipt, db_pool.clone()).await {
+                let error_message = format!("Error indexing token transfers: {:?}", e);
+                log_error!("{}", error_message);
+                return Err(error_message); // Return the error message
+            }
+            // Same, for Approval/ApprovalForAll events so allowances/operator approvals can be
+            // reconstructed downstream.
+            if let Err(e) = db::index_approvals(&transaction_receipt, db_pool.clone()).await {
+                let error_message = format!("Error indexing token approvals: {:?}", e);
+                log_error!("{}", error_message);
+                return Err(error_message); // Return the error message
+            }
+            // Index the transaction's logs, unless `flush_batch` already bulk-inserted them.
+            if !logs_already_inserted {
+                for log in transaction_receipt.logs {
+                    if let Err(e) = db::insert_log(log, db_pool.clone(), ws_client.clone()).await {
+                        let error_message = format!("Error inserting log into database: {:?}", e);
                         log_error!("{}", error_message);
                         return Err(error_message); // Return the error message
                     }
-                    // Index the contract
-                    if let Some(contract_address) = transaction_receipt.contract_address {
-                        // Index the contract address
-                        if let Err(e) = index_address(
-                            contract_address,
-                            transaction.block_number.unwrap(),
-                            ws_client.clone(),
-                            db_pool.clone(),
-                        )
-                        .await
-                        {
-                            let error_message = format!("Error indexing contract address: {:?}", e);
-                            log_error!("{}", error_message);
-                            return Err(error_message); // Return the error message
-                        }
-                        // Index the smart contract (code and verified source code)
-                        if let Err(e) = index_smart_contract(
-                            transaction_receipt.clone(),
-                            ws_client.clone(),
-                            db_pool.clone(),
-                        )
-                        .await
-                        {
-                            let error_message =
-                                format!("Error indexing smart contract code: {:?}", e);
-                            log_error!("{}", error_message);
-                            return Err(error_message); // Return the error message
-                        }
-                    }
-                    // Index the transactions logs
-                    for log in transaction_receipt.logs {
-                        if let Err(e) =
-                            db::insert_log(log, db_pool.clone(), ws_client.clone()).await
-                        {
-                            let error_message =
-                                format!("Error inserting log into database: {:?}", e);
-                            log_error!("{}", error_message);
-                            return Err(error_message); // Return the error message
-                        }
-                    }
-                }
-                _ => {
-                    log_error!("Error getting transaction receipt {}", transaction_hash);
-                    return Ok(()); // Return the error message
                 }
-            };
+            }
         }
-        _ => log_error!("Error indexing transaction {}", transaction_hash),
-    }
+        _ => {
+            log_error!("Error getting transaction receipt {}", transaction_hash);
+            return Ok(()); // Return the error message
+        }
+    };
 
     Ok(())
 }
@@ -457,11 +968,16 @@ async fn index_address(
     address: Address,
     block_number: U64,
     ws_client: Arc<Provider<Ws>>,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), String> {
     let block_id = BlockId::from(BlockNumber::from(block_number.clone()));
     // Get the balance of the address
-    let balance = match ws_client.get_balance(address, Some(block_id.clone())).await {
+    let balance_description = format!("fetching balance for address {}", address);
+    let balance = match retry::call_with_retry(&balance_description, || {
+        ws_client.get_balance(address, Some(block_id.clone()))
+    })
+    .await
+    {
         Ok(balance) => balance,
         Err(e) => {
             log_error!("Error getting balance for address {}: {}", address, e);
@@ -493,9 +1009,11 @@ async fn index_address(
 
     // Get transaction count
     // Get the nounce of the address
-    let transaction_count = match ws_client
-        .get_transaction_count(address, Some(block_id.clone()))
-        .await
+    let nonce_description = format!("fetching transaction count for address {}", address);
+    let transaction_count = match retry::call_with_retry(&nonce_description, || {
+        ws_client.get_transaction_count(address, Some(block_id.clone()))
+    })
+    .await
     {
         Ok(count) => count,
         Err(e) => {
@@ -535,7 +1053,7 @@ async fn index_address(
 async fn index_smart_contract(
     transaction_receipt: TransactionReceipt,
     ws_client: Arc<Provider<Ws>>,
-    db_pool: Pool<PostgresConnectionManager<NoTls>>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
 ) -> Result<(), String> {
     // Get the code of the address (if it is a contract)
     let code = match ws_client
@@ -552,10 +1070,10 @@ async fn index_smart_contract(
 
     // Get the verified source code of the contract
     // TODO: get the verified source code using blockscout API
-    let verified_sc_data = blockscout::get_verified_sc_data(format!(
-        "0x{:x}",
-        transaction_receipt.contract_address.unwrap()
-    ))
+    let verified_sc_data = blockscout::get_verified_sc_data(
+        transaction_receipt.contract_address.unwrap(),
+        ws_client.clone(),
+    )
     .await;
     // let verified_sc_data = serde_json::json!({});
 
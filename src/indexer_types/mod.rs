@@ -1,75 +1,190 @@
 // Module: indexer_types
 use ethers::prelude::*;
-use ethers_contract::{EthAbiCodec, EthAbiType};
+use ethers::utils::keccak256;
+use ethers_contract::{Contract, EthAbiCodec, EthAbiType};
 use rust_decimal::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::fmt;
+use std::sync::Arc;
 
 // ContractType is an enum that represents the type of a smart contract
 pub enum ContractType {
     Unknown,
     ERC20,
     ERC721,
-    // ERC777,
-    // ERC1155,
+    ERC777,
+    ERC1155,
 }
+
+/// Function selectors every ERC-20 implementation exposes. `Transfer` alone (the old heuristic)
+/// also matches ERC-721/ERC-777, so detection has to key off the functions instead.
+const ERC20_SIGNATURES: &[&str] = &[
+    "totalSupply()",
+    "balanceOf(address)",
+    "transfer(address,uint256)",
+    "allowance(address,address)",
+    "approve(address,uint256)",
+    "transferFrom(address,address,uint256)",
+];
+
+/// Function selectors required by the ERC-721 ABI, minus `balanceOf(address)`, which ERC-20
+/// shares. `transferFrom` is shared with ERC-20 as well, which is why it alone never told the
+/// two apart before.
+const ERC721_SIGNATURES: &[&str] = &[
+    "ownerOf(uint256)",
+    "getApproved(uint256)",
+    "setApprovalForAll(address,bool)",
+    "transferFrom(address,address,uint256)",
+];
+
+/// Function selectors distinguishing ERC-777 from a plain ERC-20: the operator model and
+/// granularity have no ERC-20 equivalent.
+const ERC777_SIGNATURES: &[&str] = &[
+    "granularity()",
+    "send(address,uint256,bytes)",
+    "authorizeOperator(address)",
+    "isOperatorFor(address,address)",
+    "defaultOperators()",
+];
+
+/// Function selectors required by the ERC-1155 ABI. `balanceOf(address,uint256)` takes a token
+/// id alongside the holder, which is what tells it apart from ERC-20's `balanceOf(address)`.
+const ERC1155_SIGNATURES: &[&str] = &[
+    "balanceOf(address,uint256)",
+    "balanceOfBatch(address[],uint256[])",
+    "safeTransferFrom(address,address,uint256,uint256,bytes)",
+    "safeBatchTransferFrom(address,address,uint256[],uint256[],bytes)",
+    "setApprovalForAll(address,bool)",
+];
+
+/// ERC-165 interface ids `supportsInterface(bytes4)` is checked against. Only ERC-721 and
+/// ERC-1155 register one; ERC-20/777 predate ERC-165 and have no canonical id.
+const ERC165_INTERFACE_IDS: &[([u8; 4], fn() -> ContractType)] = &[
+    ([0x80, 0xac, 0x58, 0xcd], || ContractType::ERC721),
+    ([0xd9, 0xb6, 0x7a, 0x26], || ContractType::ERC1155),
+];
+
 impl ContractType {
     pub fn to_string(&self) -> String {
         match self {
             ContractType::Unknown => String::from(""),
             ContractType::ERC20 => String::from("ERC20"),
             ContractType::ERC721 => String::from("ERC721"),
-            // ContractType::ERC777 => String::from("ERC777"),
-            // ContractType::ERC1155 => String::from("ERC1155"),
+            ContractType::ERC777 => String::from("ERC777"),
+            ContractType::ERC1155 => String::from("ERC1155"),
         }
     }
-    pub fn detect_contract_type(abi_json: serde_json::Value) -> ContractType {
-        let mut contract_type = ContractType::Unknown;
+
+    /// Detect a contract's token standard from its ABI, preferring an on-chain ERC-165
+    /// `supportsInterface` call over the selector heuristic whenever the ABI declares that
+    /// function: the heuristic can only ever check for a required subset of functions, while
+    /// `supportsInterface` is the contract's own word for it.
+    ///
+    /// Detection previously flagged ERC-721 on the presence of a `Transfer` event alone, which
+    /// ERC-20 also emits, and never recognised ERC-777/ERC-1155 at all. Matching on 4-byte
+    /// function selectors (`keccak256(signature)[..4]`) instead means a contract has to actually
+    /// implement the interface's functions, not just emit one overlapping event.
+    pub async fn detect_contract_type(
+        abi_json: serde_json::Value,
+        address: Address,
+        ws_client: Arc<Provider<Ws>>,
+    ) -> ContractType {
         if abi_json.is_null() {
-            return contract_type;
+            return ContractType::Unknown;
         }
 
         let abi_str = abi_json.as_str().expect("ABI is not a string");
         let parsed_abi: serde_json::Value =
-            serde_json::from_str(&abi_str).expect("Failed to parse ABI JSON");
-
-        // Check for ERC-20 functions
-        let erc20_functions = vec!["totalSupply", "balanceOf", "transfer"];
-        let erc721_events = vec!["Transfer"];
-
-        fn all_names_found(parsed_abi: &serde_json::Value, names_to_check: &[&str]) -> bool {
-            let mut found_names = Vec::new();
-
-            // Iterate over the array and check "name" fields
-            if let Some(abi_array) = parsed_abi.as_array() {
-                for abi_object in abi_array {
-                    if let Some(obj_type) = abi_object["type"].as_str() {
-                        if obj_type == "function" {
-                            if let Some(name) = abi_object["name"].as_str() {
-                                found_names.push(name);
-                            }
-                        }
-                    }
+            serde_json::from_str(abi_str).expect("Failed to parse ABI JSON");
+        let selectors = function_selectors(&parsed_abi);
+
+        if has_selector(&selectors, "supportsInterface(bytes4)") {
+            if let Ok(abi) = serde_json::from_value::<Abi>(parsed_abi.clone()) {
+                let contract = Contract::new(address, abi, ws_client);
+                if let Some(contract_type) = detect_via_supports_interface(&contract).await {
+                    return contract_type;
                 }
             }
-
-            // Check if all names_to_check are found in found_names
-            names_to_check
-                .iter()
-                .all(|&name| found_names.contains(&name))
         }
 
-        // Check if all ERC-20 functions are found
-        if all_names_found(&parsed_abi, &erc20_functions) {
-            contract_type = ContractType::ERC20;
+        if has_all_selectors(&selectors, ERC20_SIGNATURES) {
+            ContractType::ERC20
+        } else if has_all_selectors(&selectors, ERC721_SIGNATURES) {
+            ContractType::ERC721
+        } else if has_all_selectors(&selectors, ERC1155_SIGNATURES) {
+            ContractType::ERC1155
+        } else if has_all_selectors(&selectors, ERC777_SIGNATURES) {
+            ContractType::ERC777
+        } else {
+            ContractType::Unknown
         }
-        // Check if all ERC-721 events are found
-        else if all_names_found(&parsed_abi, &erc721_events) {
-            contract_type = ContractType::ERC721;
+    }
+}
+
+/// Ask the contract itself via `supportsInterface(bytes4)`, trying each known interface id in
+/// turn. Returns `None` if the call errors (some `supportsInterface`-shaped functions aren't
+/// actually ERC-165-compliant) or no known interface id comes back `true`.
+async fn detect_via_supports_interface(contract: &Contract<Provider<Ws>>) -> Option<ContractType> {
+    for (interface_id, contract_type) in ERC165_INTERFACE_IDS {
+        match contract.method::<_, bool>("supportsInterface", *interface_id) {
+            Ok(method) => {
+                if let Ok(true) = method.call().await {
+                    return Some(contract_type());
+                }
+            }
+            Err(_) => continue,
         }
+    }
+    None
+}
+
+/// Compute the 4-byte selector of every function the ABI declares.
+fn function_selectors(parsed_abi: &serde_json::Value) -> HashSet<[u8; 4]> {
+    let mut selectors = HashSet::new();
+
+    let Some(abi_array) = parsed_abi.as_array() else {
+        return selectors;
+    };
 
-        contract_type
+    for abi_object in abi_array {
+        if abi_object["type"].as_str() != Some("function") {
+            continue;
+        }
+        let Some(name) = abi_object["name"].as_str() else {
+            continue;
+        };
+        let input_types: Vec<&str> = abi_object["inputs"]
+            .as_array()
+            .map(|inputs| {
+                inputs
+                    .iter()
+                    .filter_map(|input| input["type"].as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let signature = format!("{}({})", name, input_types.join(","));
+        selectors.insert(selector_of(&signature));
     }
+
+    selectors
+}
+
+/// The 4-byte function selector for a canonical signature like `"transfer(address,uint256)"`.
+fn selector_of(signature: &str) -> [u8; 4] {
+    keccak256(signature.as_bytes())[..4]
+        .try_into()
+        .expect("keccak256 output is always 32 bytes")
+}
+
+fn has_selector(selectors: &HashSet<[u8; 4]>, signature: &str) -> bool {
+    selectors.contains(&selector_of(signature))
+}
+
+fn has_all_selectors(selectors: &HashSet<[u8; 4]>, signatures: &[&str]) -> bool {
+    signatures
+        .iter()
+        .all(|signature| has_selector(selectors, signature))
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -187,10 +302,44 @@ impl TokenInfo {
     }
 }
 
-// ERC20 event Transfer(address,address,uint256)
+// ERC20/ERC721 event Transfer(address,address,uint256); `value` doubles as `tokenId` when the
+// event is decoded off an ERC-721's three-indexed-topic form.
 #[derive(Debug, Clone, EthAbiType, EthAbiCodec)]
-pub struct Transfert {
+pub struct Transfer {
     pub from: Address,
     pub to: Address,
     pub value: U256,
 }
+
+// ERC-1155 event TransferSingle(address indexed operator, address indexed from, address indexed
+// to, uint256 id, uint256 value). All three addresses are indexed and `id`/`value` are static
+// types, so it decodes the same way `Transfer` does: indexed topics and `data` concatenated back
+// into the tuple's original encoding.
+#[derive(Debug, Clone, EthAbiType, EthAbiCodec)]
+pub struct TransferSingle {
+    pub operator: Address,
+    pub from: Address,
+    pub to: Address,
+    pub id: U256,
+    pub value: U256,
+}
+
+// ERC20/ERC721 event Approval(address,address,uint256); like `Transfer`, `value` doubles as the
+// approved `tokenId` when decoded off an ERC-721's three-indexed-topic form.
+#[derive(Debug, Clone, EthAbiType, EthAbiCodec)]
+pub struct Approval {
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+}
+
+// ERC-721/ERC-1155 event ApprovalForAll(address indexed owner, address indexed operator, bool
+// approved). Both addresses are indexed and `approved` is a static `bool`, so it decodes the same
+// way `Transfer` does: indexed topics and `data` concatenated back into the tuple's original
+// encoding.
+#[derive(Debug, Clone, EthAbiType, EthAbiCodec)]
+pub struct ApprovalForAll {
+    pub owner: Address,
+    pub operator: Address,
+    pub approved: bool,
+}
@@ -4,11 +4,18 @@
 ///
 /// main.rs
 use std::env;
+mod abi_decoder;
 mod blockscout;
 mod db;
 mod indexer;
 mod indexer_types;
+mod metrics;
+mod proxy;
+mod retry;
 mod rpc;
+mod server;
+mod token_filter;
+mod verified_source;
 use crate::indexer::Indexer;
 pub use indexer_types::*;
 use log::{info, warn};
@@ -23,6 +30,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     create_pid_file()?;
     check_env();
     load_env();
+
+    let metrics_addr: std::net::SocketAddr = env::var("METRICS_ADDR")
+        .unwrap_or_else(|_| "0.0.0.0:9000".to_string())
+        .parse()
+        .expect("METRICS_ADDR must be a valid socket address");
+    tokio::spawn(metrics::serve_metrics(metrics_addr));
+
     let args: Vec<String> = env::args().collect();
 
     match args.len() {
@@ -36,7 +50,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 warn!("Starting indexer");
                 let indexer = Indexer::new().await;
                 warn!("MODE: index_all");
-                indexer.run().await?;
+                indexer.run(graceful_shutdown_signal()).await?;
             }
             "index_live" => {
                 warn!("Starting live indexer");
@@ -54,6 +68,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     _ = indexer.run_live() => {}
                 }
             }
+            "index_backfill" => {
+                warn!("Starting gap backfill");
+                warn!("MODE: index_backfill");
+                let indexer = Indexer::new().await;
+                indexer.run_backfill(graceful_shutdown_signal()).await?;
+            }
+            "index_retry_failed" => {
+                warn!("Starting failed-block retry");
+                warn!("MODE: index_retry_failed");
+                let indexer = Indexer::new().await;
+                indexer.run_retry_failed(graceful_shutdown_signal()).await?;
+            }
+            "index_serve" => {
+                warn!("Starting query server");
+                warn!("MODE: index_serve");
+                let indexer = Indexer::new().await;
+                let serve_addr: std::net::SocketAddr = env::var("SERVE_ADDR")
+                    .unwrap_or_else(|_| "0.0.0.0:9100".to_string())
+                    .parse()
+                    .expect("SERVE_ADDR must be a valid socket address");
+                server::serve_query_api(serve_addr, indexer.db_pool()).await;
+            }
             "help" | "--help" | "-h" | "-v" | "--version" => {
                 help();
             }
@@ -69,7 +105,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 let indexer = Indexer::new().await;
                 let number_of_blocks: u64 = args[2].parse().unwrap();
                 warn!("{}", format!("MODE: index_last {}", number_of_blocks));
-                indexer.run_last_blocks(number_of_blocks).await?;
+                indexer
+                    .run_last_blocks(number_of_blocks, graceful_shutdown_signal())
+                    .await?;
             }
             "index_last_hours" => {
                 warn!("Starting indexer");
@@ -80,7 +118,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // 3600 * 1/6 = 600 blocks per hour
                 let number_of_hours: u64 = args[2].parse().unwrap();
                 warn!("{}", format!("MODE: index_last_hours {}", number_of_hours));
-                indexer.run_last_blocks(number_of_hours * 600).await?;
+                indexer
+                    .run_last_blocks(number_of_hours * 600, graceful_shutdown_signal())
+                    .await?;
             }
             "index_last_days" => {
                 warn!("Starting indexer");
@@ -92,7 +132,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // 1 day = 24 hours
                 let number_of_days: u64 = args[2].parse().unwrap();
                 warn!("{}", format!("MODE: index_last_days {}", number_of_days));
-                indexer.run_last_blocks(number_of_days * 24 * 600).await?;
+                indexer
+                    .run_last_blocks(number_of_days * 24 * 600, graceful_shutdown_signal())
+                    .await?;
             }
             _ => {
                 println!("'{}' is not a valid argument", args[1]);
@@ -107,8 +149,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Watch for CTRL+C and flip a `watch` channel to `true` when it fires, returning the receiving
+/// end so a backfill pipeline can stop scheduling new work and flush whatever it already has
+/// buffered instead of being killed mid-batch. Unlike `index_live`'s `tokio::select!` (which has
+/// no in-memory batch to lose), a historical backfill's write pipeline needs to keep running
+/// after the signal to drain and flush, so this notifies it rather than racing it.
+fn graceful_shutdown_signal() -> tokio::sync::watch::Receiver<bool> {
+    let (tx, rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        if signal::ctrl_c().await.is_ok() {
+            warn!("Received exit signal, finishing the current batch and shutting down...");
+            let _ = tx.send(true);
+        }
+    });
+    rx
+}
+
 fn help() {
-    println!("\nUsage: eth-indexer [index_all|index_live|help|index_last <NB_BLOCKS>|index_last_hours <NB_HOURS>|index_last_days <NB_DAYS>]\n");
+    println!("\nUsage: eth-indexer [index_all|index_live|index_backfill|index_retry_failed|index_serve|help|index_last <NB_BLOCKS>|index_last_hours <NB_HOURS>|index_last_days <NB_DAYS>]\n");
     // print an example
     println!("Example: eth-indexer index_last_days 1\n");
     let version = env!("CARGO_PKG_VERSION");
@@ -148,7 +206,6 @@ fn check_env() {
     info!("Configuration:");
     // Check all the environment variables are set
     let env_vars = vec![
-        "VERSION",
         "HTTP_RPC_ENDPOINT",
         "WS_RPC_ENDPOINT",
         "POSTGRES_HOST",
@@ -156,7 +213,6 @@ fn check_env() {
         "POSTGRES_USER",
         "POSTGRES_PASSWORD",
         "POSTGRES_DATABASE",
-        "POSTGRES_CREATE_TABLE_ORDER",
         "NB_OF_WS_CONNECTIONS",
         "NB_OF_DB_CONNECTIONS",
         "START_BLOCK",
@@ -0,0 +1,135 @@
+// Module: metrics
+//
+// The only observability the indexer had was the `info!("Blocks per second: ...")` log line in
+// `indexer::run_write_pipeline`. This registers the counters/gauges the rest of the crate updates
+// as it indexes, and serves them in Prometheus text format over HTTP so the indexer can be
+// scraped instead of grepped.
+
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Response, Server};
+use lazy_static::lazy_static;
+use log::{error as log_error, info};
+use prometheus::{
+    register_histogram, register_int_counter, register_int_gauge, Encoder, Histogram, IntCounter,
+    IntGauge, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use crate::db::DbTlsConnector;
+
+lazy_static! {
+    /// Total number of blocks inserted into `blocks`.
+    pub static ref BLOCKS_INDEXED: IntCounter = register_int_counter!(
+        "eth_indexer_blocks_indexed_total",
+        "Total number of blocks indexed"
+    )
+    .unwrap();
+    /// Total number of transactions inserted into `transactions`.
+    pub static ref TRANSACTIONS_INDEXED: IntCounter = register_int_counter!(
+        "eth_indexer_transactions_indexed_total",
+        "Total number of transactions indexed"
+    )
+    .unwrap();
+    /// Total number of ERC-20, ERC-721 and ERC-1155 transfer rows written to `token_transfers`.
+    pub static ref TOKEN_TRANSFERS_INDEXED: IntCounter = register_int_counter!(
+        "eth_indexer_token_transfers_indexed_total",
+        "Total number of token transfers indexed"
+    )
+    .unwrap();
+    /// Total number of logs inserted into `logs`.
+    pub static ref LOGS_INDEXED: IntCounter = register_int_counter!(
+        "eth_indexer_logs_indexed_total",
+        "Total number of logs indexed"
+    )
+    .unwrap();
+    /// Difference between the latest chain head and the last block written to the database.
+    pub static ref HEAD_LAG: IntGauge = register_int_gauge!(
+        "eth_indexer_head_lag",
+        "Number of blocks between the chain head and the last indexed block"
+    )
+    .unwrap();
+    /// Number of block/transaction fetches currently in flight in the fetch pipeline.
+    pub static ref IN_FLIGHT_FETCHES: IntGauge = register_int_gauge!(
+        "eth_indexer_in_flight_fetches",
+        "Number of block/transaction fetches currently in flight"
+    )
+    .unwrap();
+    /// Total number of database insert errors across every insert path.
+    pub static ref DB_INSERT_ERRORS: IntCounter = register_int_counter!(
+        "eth_indexer_db_insert_errors_total",
+        "Total number of database insert errors"
+    )
+    .unwrap();
+    /// Total number of retry attempts made by `retry::call_with_retry` after a transient failure.
+    pub static ref RETRY_ATTEMPTS: IntCounter = register_int_counter!(
+        "eth_indexer_retry_attempts_total",
+        "Total number of retry attempts after a transient RPC/DB error"
+    )
+    .unwrap();
+    /// Total number of blocks that exhausted retries and were recorded in `failed_blocks`.
+    pub static ref FAILED_BLOCKS: IntCounter = register_int_counter!(
+        "eth_indexer_failed_blocks_total",
+        "Total number of blocks recorded in the failed-blocks dead-letter queue"
+    )
+    .unwrap();
+    /// Wall-clock time to fully index a single block: `index_block`'s whole body for
+    /// `run_live`/`run_backfill`/`run_retry_failed`, or `flush_batch`'s average-per-block
+    /// duration for the bulk backfill path (see `index_blocks`), since that path doesn't time
+    /// individual blocks.
+    pub static ref BLOCK_INDEX_LATENCY_SECONDS: Histogram = register_histogram!(
+        "eth_indexer_block_index_latency_seconds",
+        "Time to fully index a single block"
+    )
+    .unwrap();
+    /// Connections currently checked out of the database pool, polled by `track_db_pool`.
+    pub static ref DB_POOL_CONNECTIONS_IN_USE: IntGauge = register_int_gauge!(
+        "eth_indexer_db_pool_connections_in_use",
+        "Number of connections currently checked out of the database pool"
+    )
+    .unwrap();
+    /// Configured maximum size of the database pool, polled by `track_db_pool`.
+    pub static ref DB_POOL_CONNECTIONS_MAX: IntGauge = register_int_gauge!(
+        "eth_indexer_db_pool_connections_max",
+        "Maximum number of connections configured for the database pool"
+    )
+    .unwrap();
+}
+
+/// Periodically poll `db_pool`'s `bb8::State` (connections currently handed out vs. idle) and
+/// publish it as `DB_POOL_CONNECTIONS_IN_USE`/`DB_POOL_CONNECTIONS_MAX` (the latter just mirrors
+/// `db::connect_db`'s configured `DB_POOL_MAX_SIZE`, passed in as `max_size` since `bb8::Pool`
+/// doesn't expose its own builder config back out), since pool saturation isn't something any
+/// single insert call site can observe on its own. Runs until the process exits; spawned once
+/// from `Indexer::new` alongside the pool itself.
+pub async fn track_db_pool(db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>, max_size: u32) {
+    DB_POOL_CONNECTIONS_MAX.set(max_size as i64);
+    loop {
+        let state = db_pool.state();
+        DB_POOL_CONNECTIONS_IN_USE.set((state.connections - state.idle_connections) as i64);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+/// Serve the Prometheus metrics registry in text format at `http://<addr>/metrics`.
+pub async fn serve_metrics(addr: SocketAddr) {
+    let make_svc = make_service_fn(|_conn| async {
+        Ok::<_, std::convert::Infallible>(service_fn(|_req| async {
+            let metric_families = prometheus::gather();
+            let mut buffer = Vec::new();
+            let encoder = TextEncoder::new();
+            if let Err(e) = encoder.encode(&metric_families, &mut buffer) {
+                log_error!("Error encoding metrics: {}", e);
+            }
+            Ok::<_, std::convert::Infallible>(Response::new(Body::from(buffer)))
+        }))
+    });
+
+    info!("Serving metrics on http://{}/metrics", addr);
+    let server = Server::bind(&addr).serve(make_svc);
+    if let Err(e) = server.await {
+        log_error!("Metrics server error: {}", e);
+    }
+}
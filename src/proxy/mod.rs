@@ -0,0 +1,73 @@
+// Module: proxy
+//
+// `get_verified_sc_data` only ever looked at a contract's own bytecode, so a proxy — the vast
+// majority of real ERC20/721 deployments, built on an OpenZeppelin
+// TransparentUpgradeableProxy/UUPS or a minimal-proxy clone factory — only ever got its own
+// near-empty forwarding ABI indexed, never the implementation it actually delegates calls to.
+// This resolves a contract's implementation address via the two storage-slot conventions
+// (EIP-1967, legacy EIP-1822) and the EIP-1167 minimal-proxy bytecode template.
+
+use ethers::prelude::*;
+use std::sync::Arc;
+
+/// `bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)` — EIP-1967's storage slot
+/// for a transparent/UUPS proxy's implementation address.
+const EIP1967_IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// `keccak256('PROXIABLE')` — the legacy EIP-1822 storage slot some pre-EIP-1967 UUPS proxies
+/// still use.
+const EIP1822_IMPLEMENTATION_SLOT: &str =
+    "0xc5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7";
+
+/// EIP-1167 minimal-proxy runtime bytecode, with the cloned implementation address spliced in
+/// between the fixed prefix and suffix:
+/// `363d3d373d3d3d363d73<20-byte address>5af43d82803e903d91602b57fd5bf3`.
+const MINIMAL_PROXY_PREFIX: &[u8] = &[0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+const MINIMAL_PROXY_SUFFIX: &[u8] = &[
+    0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3,
+];
+
+/// Resolve `address`'s implementation contract, if it is a proxy. Tries, in order: an EIP-1167
+/// minimal-proxy bytecode match against `code` (no RPC call needed), then the EIP-1967 and
+/// legacy EIP-1822 implementation storage slots via `ws_client.get_storage_at`. Returns `None`
+/// if none of the three match, or the matched slot/bytecode resolves to the zero address (an
+/// unset proxy).
+pub async fn resolve_implementation(
+    address: Address,
+    code: &Bytes,
+    ws_client: Arc<Provider<Ws>>,
+) -> Option<Address> {
+    if let Some(implementation) =
+        minimal_proxy_implementation(code).filter(|implementation| !implementation.is_zero())
+    {
+        return Some(implementation);
+    }
+
+    for slot in [EIP1967_IMPLEMENTATION_SLOT, EIP1822_IMPLEMENTATION_SLOT] {
+        let slot: H256 = slot.parse().expect("hardcoded proxy slot is valid H256");
+        match ws_client.get_storage_at(address, slot, None).await {
+            Ok(value) if value != H256::zero() => return Some(Address::from(value)),
+            _ => continue,
+        }
+    }
+
+    None
+}
+
+/// Match `code` against the EIP-1167 minimal-proxy template and, on a match, extract the
+/// 20-byte implementation address spliced in between the fixed prefix/suffix.
+fn minimal_proxy_implementation(code: &Bytes) -> Option<Address> {
+    let expected_len = MINIMAL_PROXY_PREFIX.len() + 20 + MINIMAL_PROXY_SUFFIX.len();
+    if code.len() != expected_len {
+        return None;
+    }
+
+    let (prefix, rest) = code.split_at(MINIMAL_PROXY_PREFIX.len());
+    let (implementation, suffix) = rest.split_at(20);
+    if prefix != MINIMAL_PROXY_PREFIX || suffix != MINIMAL_PROXY_SUFFIX {
+        return None;
+    }
+
+    Some(Address::from_slice(implementation))
+}
@@ -0,0 +1,95 @@
+// Module: retry
+//
+// Centralizes the "is this worth trying again?" + backoff policy for `index_block`,
+// `index_transaction`, and `index_address`'s RPC/DB calls, instead of each call site either
+// swallowing the error outright or guessing its own retry logic. Transient failures (the
+// endpoint hiccuped, the connection dropped) are retried with exponential backoff and jitter up
+// to `RETRY_MAX_ATTEMPTS`; permanent ones (a malformed request, a block that doesn't exist) fail
+// immediately since retrying them would just waste the attempt budget.
+
+use log::warn;
+use rand::Rng;
+use std::env;
+use std::future::Future;
+use std::time::Duration;
+
+/// Whether a failure is worth retrying.
+#[derive(Debug, PartialEq, Eq)]
+enum ErrorClass {
+    /// Likely to succeed on a later attempt: a timeout, a dropped connection, a rate limit.
+    Transient,
+    /// Retrying wouldn't help: anything else.
+    Permanent,
+}
+
+/// Classify an error by matching common transient-failure wording in its message. This is a
+/// heuristic rather than a typed distinction — `ProviderError`'s `JsonRpcClientError` variant
+/// boxes an opaque source error, and `tokio-postgres`'s `Error` is similarly opaque once it's
+/// crossed an `await` boundary — but it covers the failure modes this indexer actually sees
+/// against a flaky RPC endpoint or a restarting database.
+fn classify_error(message: &str) -> ErrorClass {
+    const TRANSIENT_PATTERNS: &[&str] = &[
+        "timeout",
+        "timed out",
+        "connection reset",
+        "connection refused",
+        "connection closed",
+        "broken pipe",
+        "rate limit",
+        "too many requests",
+        "unexpected eof",
+        "temporarily unavailable",
+        "temporary failure",
+    ];
+    let message = message.to_lowercase();
+    if TRANSIENT_PATTERNS.iter().any(|pattern| message.contains(pattern)) {
+        ErrorClass::Transient
+    } else {
+        ErrorClass::Permanent
+    }
+}
+
+/// Retry `operation` with exponential backoff and jitter while its error classifies as
+/// transient, up to `RETRY_MAX_ATTEMPTS` (env, default 5) attempts total. Stops immediately,
+/// without sleeping, the first time the error classifies as permanent.
+///
+/// `description` is only used for logging, e.g. `"fetching block 123"`.
+pub async fn call_with_retry<T, E, F, Fut>(description: &str, mut operation: F) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let max_attempts: u32 = env::var("RETRY_MAX_ATTEMPTS")
+        .unwrap_or_else(|_| "5".to_string())
+        .parse()
+        .unwrap_or(5)
+        .max(1);
+    let base_delay_ms: u64 = env::var("RETRY_BASE_DELAY_MS")
+        .unwrap_or_else(|_| "200".to_string())
+        .parse()
+        .unwrap_or(200);
+
+    let mut attempt = 0u32;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                attempt += 1;
+                let message = e.to_string();
+                if attempt >= max_attempts || classify_error(&message) == ErrorClass::Permanent {
+                    return Err(e);
+                }
+                crate::metrics::RETRY_ATTEMPTS.inc();
+                let backoff_ms = base_delay_ms.saturating_mul(1u64 << (attempt - 1));
+                let jitter_ms = rand::thread_rng().gen_range(0..=backoff_ms.max(1));
+                let delay = Duration::from_millis(backoff_ms + jitter_ms);
+                warn!(
+                    "{} failed (attempt {}/{}): {}. Retrying in {:?}",
+                    description, attempt, max_attempts, message, delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
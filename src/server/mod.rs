@@ -0,0 +1,89 @@
+// Module: server
+//
+// `index_serve` turns the indexer into a read-only query service: a thin hyper layer over
+// `db::query`'s lookups, so a consumer can fetch already-indexed data as JSON instead of writing
+// SQL against the database directly. Mirrors `metrics::serve_metrics`'s use of hyper's low-level
+// `service_fn` rather than pulling in a routing framework for four endpoints.
+use crate::db::{self, DbTlsConnector};
+use bb8::Pool;
+use bb8_postgres::PostgresConnectionManager;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use log::{error as log_error, info};
+use serde_json::Value;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+/// Serve the read-only query API at `http://<addr>/...`:
+/// - `GET /transactions/:hash`
+/// - `GET /transactions/:hash/receipt`
+/// - `GET /blocks/:number_or_hash/transactions`
+/// - `GET /addresses/:address/token_transfers`
+pub async fn serve_query_api(addr: SocketAddr, db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>) {
+    let make_svc = make_service_fn(move |_conn| {
+        let db_pool = db_pool.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let db_pool = db_pool.clone();
+                async move { Ok::<_, Infallible>(route(req, db_pool).await) }
+            }))
+        }
+    });
+
+    info!("Serving query API on http://{}", addr);
+    let server = Server::bind(&addr).serve(make_svc);
+    if let Err(e) = server.await {
+        log_error!("Query API server error: {}", e);
+    }
+}
+
+/// Dispatch a request to the matching `db::query` lookup and turn its result into a response.
+/// A lookup returning `Value::Null` (the single-item endpoints' "not found" sentinel) becomes a
+/// 404; the list endpoints never return `Null`, so an empty match there is a `200` with `[]`.
+async fn route(
+    req: Request<Body>,
+    db_pool: Pool<PostgresConnectionManager<DbTlsConnector>>,
+) -> Response<Body> {
+    let path_segments: Vec<&str> = req.uri().path().trim_matches('/').split('/').collect();
+
+    let result = match (req.method(), path_segments.as_slice()) {
+        (&Method::GET, ["transactions", hash]) => db::get_transaction_by_hash(hash, db_pool).await,
+        (&Method::GET, ["transactions", hash, "receipt"]) => {
+            db::get_transaction_receipt_by_hash(hash, db_pool).await
+        }
+        (&Method::GET, ["blocks", number_or_hash, "transactions"]) => {
+            db::get_block_transactions(number_or_hash, db_pool).await
+        }
+        (&Method::GET, ["addresses", address, "token_transfers"]) => {
+            db::get_token_transfers_for_address(address, db_pool).await
+        }
+        _ => return not_found(),
+    };
+
+    match result {
+        Ok(value) if value.is_null() => not_found(),
+        Ok(value) => json_response(StatusCode::OK, &value),
+        Err(e) => {
+            log_error!("Query API error: {}", e);
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("Internal server error"))
+                .unwrap()
+        }
+    }
+}
+
+fn not_found() -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from("Not found"))
+        .unwrap()
+}
+
+fn json_response(status: StatusCode, value: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(value.to_string()))
+        .unwrap()
+}
@@ -0,0 +1,105 @@
+// Module: token_filter
+//
+// `logs::insert_log`/`insert_logs_bulk` and `transactions::decode_input` run a DB lookup plus an
+// ABI decode attempt for every single log/transaction, even on chains where an operator only
+// cares about a handful of known token contracts. This loads an optional TOML config (path in env
+// `TOKEN_FILTER_CONFIG`) listing which addresses are worth that cost, so those call sites can
+// short-circuit before ever touching `contracts::get_abi_json`. No config set means "index
+// everything", same as today.
+
+use ethers::types::Address;
+use lazy_static::lazy_static;
+use log::{info, warn};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::str::FromStr;
+
+const CONFIG_PATH_ENV: &str = "TOKEN_FILTER_CONFIG";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FilterMode {
+    /// Only the listed addresses are indexed.
+    Whitelist,
+    /// Every address is indexed except the listed ones.
+    Blacklist,
+}
+
+/// Shape of the `TOKEN_FILTER_CONFIG` TOML file, e.g.:
+/// ```toml
+/// mode = "whitelist"
+/// addresses = [
+///     "0xdAC17F958D2ee523a2206206994597C13D831ec7",
+///     "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+/// ]
+/// ```
+#[derive(Debug, Deserialize)]
+struct TokenFilterConfig {
+    mode: FilterMode,
+    addresses: Vec<String>,
+}
+
+struct TokenFilter {
+    mode: FilterMode,
+    addresses: HashSet<Address>,
+}
+
+lazy_static! {
+    /// Parsed once at first use; `None` if `TOKEN_FILTER_CONFIG` is unset or unreadable/invalid,
+    /// in which case `should_index` allows everything.
+    static ref FILTER: Option<TokenFilter> = load_filter();
+}
+
+fn load_filter() -> Option<TokenFilter> {
+    let path = env::var(CONFIG_PATH_ENV).ok()?;
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Could not read {} ({}): {}", CONFIG_PATH_ENV, path, e);
+            return None;
+        }
+    };
+    let config: TokenFilterConfig = match toml::from_str(&contents) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Could not parse {} ({}): {}", CONFIG_PATH_ENV, path, e);
+            return None;
+        }
+    };
+
+    let addresses: HashSet<Address> = config
+        .addresses
+        .iter()
+        .filter_map(|address| match Address::from_str(address) {
+            Ok(address) => Some(address),
+            Err(e) => {
+                warn!("Skipping invalid address {:?} in {}: {}", address, path, e);
+                None
+            }
+        })
+        .collect();
+
+    info!(
+        "Loaded token filter from {} ({:?} mode, {} addresses)",
+        path,
+        config.mode,
+        addresses.len()
+    );
+
+    Some(TokenFilter { mode: config.mode, addresses })
+}
+
+/// Whether `address` is worth the ABI-fetch + decode path. Always `true` when no
+/// `TOKEN_FILTER_CONFIG` is configured.
+pub fn should_index(address: Address) -> bool {
+    match FILTER.as_ref() {
+        None => true,
+        Some(filter) => match filter.mode {
+            FilterMode::Whitelist => filter.addresses.contains(&address),
+            FilterMode::Blacklist => !filter.addresses.contains(&address),
+        },
+    }
+}
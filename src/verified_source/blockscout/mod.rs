@@ -0,0 +1,105 @@
+// Module: verified_source::blockscout
+//
+// The original Blockscout-specific fetch that used to live directly in `crate::blockscout`,
+// moved behind the `VerifiedSourceProvider` trait so it can be tried alongside/after other
+// backends instead of being the only option.
+
+use crate::indexer_types::{self, ContractType};
+use crate::verified_source::VerifiedSourceProvider;
+use ethers::prelude::*;
+use log::{debug, error as log_error};
+use reqwest::Client;
+use serde_json::Value;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub struct BlockscoutProvider;
+
+impl VerifiedSourceProvider for BlockscoutProvider {
+    fn fetch<'a>(
+        &'a self,
+        address: Address,
+        ws_client: Arc<Provider<Ws>>,
+    ) -> Pin<Box<dyn Future<Output = Option<indexer_types::ContractInfo>> + Send + 'a>> {
+        Box::pin(fetch(address, ws_client))
+    }
+}
+
+/// Function to connect to the blockscout REST API endpoint
+/// Returns the endpoint, the API key and a client
+fn connect_blockscout() -> (String, String, Client) {
+    let blockscout_endpoint = env::var("BLOCKSCOUT_ENDPOINT").unwrap();
+    let blockscout_api_key = env::var("BLOCKSCOUT_API_KEY").unwrap();
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .unwrap();
+    (blockscout_endpoint, blockscout_api_key, client)
+}
+
+async fn fetch(address: Address, ws_client: Arc<Provider<Ws>>) -> Option<indexer_types::ContractInfo> {
+    let (blockscout_endpoint, _blockscout_api_key, client) = connect_blockscout();
+    let address_str = format!("0x{:x}", address);
+    let url = format!(
+        "{}/api/v2/smart-contracts/{}", //&apikey={}
+        blockscout_endpoint, address_str //, blockscout_api_key
+    );
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            log_error!("Error requesting verified source code for {}: {}", address_str, e);
+            return None;
+        }
+    };
+    // check response code is 200
+    if response.status().is_client_error() {
+        debug!("No verified source code found for {}", address_str);
+        return None;
+    }
+    // if other than 200 and 404, log error
+    if response.status().is_server_error() {
+        log_error!("Error getting verified source code for {}: {:?}", address_str, response);
+        return None;
+    }
+    // Deserialize the JSON response into the ContractInfo struct
+    let json = match response.json::<Value>().await {
+        Ok(result) => result,
+        Err(e) => {
+            log_error!("Error parsing JSON");
+            log_error!("Error: {}", e);
+            serde_json::from_value(serde_json::json!([])).unwrap()
+        }
+    };
+    // check if json has result field and if it is not empty
+    if json.is_null() {
+        debug!("Error smart contract JSON is null");
+        return None;
+    }
+    // Serialize the ContractInfo struct with specific field names
+    let res = indexer_types::ContractInfo {
+        contractType: ContractType::detect_contract_type(json["abi"].clone(), address, ws_client)
+            .await
+            .to_string(),
+        abi_json: json["abi"].clone(),
+        abi: json["abi"].clone().to_string(),
+        additionalSources: json["additional_sources"].clone().to_string(),
+        compilerSettings: json["compiler_settings"].clone().to_string(),
+        compilerVersion: json["compiler_version"].clone().to_string(),
+        constructorArguments: json["constructor_args"].clone().to_string(),
+        contractName: json["name"].clone().to_string(),
+        EVMVersion: json["evm_version"].clone().to_string(),
+        fileName: json["file_path"].clone().to_string(),
+        isProxy: false, //json["IsProxy"].clone().to_string() == "true",
+        optimizationUsed: json["optimization_enabled"].clone().to_string() == "true",
+        sourceCode: json["source_code"].clone().to_string(),
+    };
+
+    if res.is_null() {
+        return None;
+    }
+
+    debug!("Got verified source code for {}", address);
+    Some(res)
+}
@@ -0,0 +1,98 @@
+// Module: verified_source::etherscan
+//
+// Etherscan-compatible explorers (Etherscan itself, and the many block explorers that clone its
+// API) expose a single `?module=contract&action=getsourcecode&address=...` endpoint returning
+// `{status, message, result: [{...}]}`, where `status == "0"` means "not verified" rather than an
+// HTTP error.
+
+use crate::indexer_types::{self, ContractType};
+use crate::verified_source::VerifiedSourceProvider;
+use ethers::prelude::*;
+use log::{debug, error as log_error};
+use reqwest::Client;
+use serde_json::Value;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub struct EtherscanProvider;
+
+impl VerifiedSourceProvider for EtherscanProvider {
+    fn fetch<'a>(
+        &'a self,
+        address: Address,
+        ws_client: Arc<Provider<Ws>>,
+    ) -> Pin<Box<dyn Future<Output = Option<indexer_types::ContractInfo>> + Send + 'a>> {
+        Box::pin(fetch(address, ws_client))
+    }
+}
+
+async fn fetch(address: Address, ws_client: Arc<Provider<Ws>>) -> Option<indexer_types::ContractInfo> {
+    let endpoint = env::var("ETHERSCAN_ENDPOINT").unwrap_or_else(|_| "https://api.etherscan.io/api".to_string());
+    let api_key = env::var("ETHERSCAN_API_KEY").unwrap_or_default();
+    let address_str = format!("0x{:x}", address);
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let url = format!(
+        "{}?module=contract&action=getsourcecode&address={}&apikey={}",
+        endpoint, address_str, api_key
+    );
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            log_error!("Error requesting Etherscan source for {}: {}", address_str, e);
+            return None;
+        }
+    };
+    if !response.status().is_success() {
+        debug!("Error getting verified source code from Etherscan for {}", address_str);
+        return None;
+    }
+    let json: Value = match response.json().await {
+        Ok(json) => json,
+        Err(e) => {
+            log_error!("Error parsing Etherscan response for {}: {}", address_str, e);
+            return None;
+        }
+    };
+
+    // `status == "0"` covers both "not verified" and rate-limit/invalid-key errors; either way
+    // there's no usable source to index.
+    if json["status"].as_str() != Some("1") {
+        debug!("No verified source code found on Etherscan for {}", address_str);
+        return None;
+    }
+    let result = json["result"].as_array()?.first()?;
+    let source_code = result["SourceCode"].as_str().unwrap_or("").to_string();
+    if source_code.is_empty() {
+        return None;
+    }
+
+    let abi_str = result["ABI"].as_str().unwrap_or("[]");
+    let abi_json: Value = serde_json::from_str(abi_str).unwrap_or_else(|_| serde_json::json!([]));
+
+    let res = indexer_types::ContractInfo {
+        contractType: ContractType::detect_contract_type(abi_json.clone(), address, ws_client)
+            .await
+            .to_string(),
+        abi: abi_json.to_string(),
+        abi_json,
+        additionalSources: String::from(""),
+        compilerSettings: String::from(""),
+        compilerVersion: result["CompilerVersion"].as_str().unwrap_or("").to_string(),
+        constructorArguments: result["ConstructorArguments"].as_str().unwrap_or("").to_string(),
+        contractName: result["ContractName"].as_str().unwrap_or("").to_string(),
+        EVMVersion: result["EVMVersion"].as_str().unwrap_or("").to_string(),
+        fileName: String::from(""),
+        isProxy: result["Proxy"].as_str() == Some("1"),
+        optimizationUsed: result["OptimizationUsed"].as_str() == Some("1"),
+        sourceCode: source_code,
+    };
+
+    debug!("Got verified source code from Etherscan for {}", address_str);
+    Some(res)
+}
@@ -0,0 +1,73 @@
+// Module: verified_source
+//
+// `blockscout::get_verified_sc_data` used to talk to Blockscout's `/api/v2/smart-contracts/{address}`
+// endpoint directly, reading Blockscout-specific JSON keys inline. Not every chain runs a
+// Blockscout instance, so this abstracts "fetch a contract's verified ABI/source by address"
+// behind a `VerifiedSourceProvider` trait with one implementor per backend (Blockscout, Sourcify,
+// an Etherscan-compatible explorer), selected — and chained as a fallback list — via the
+// VERIFIED_SOURCE_PROVIDERS env var. Each implementor maps its own response shape into the
+// common `ContractInfo`, so the DB-insert code paths never need to know which backend answered.
+
+mod blockscout;
+mod etherscan;
+mod sourcify;
+
+pub use blockscout::BlockscoutProvider;
+pub use etherscan::EtherscanProvider;
+pub use sourcify::SourcifyProvider;
+
+use crate::indexer_types::ContractInfo;
+use ethers::prelude::*;
+use log::{debug, warn};
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// A backend that can look up a contract's verified ABI/source by address. `fetch` returns
+/// `None` when the backend has no verified source for `address` (not found, or a request error)
+/// so `fetch_verified_source` can fall through to the next configured provider instead of
+/// stopping on the first miss.
+pub trait VerifiedSourceProvider: Send + Sync {
+    fn fetch<'a>(
+        &'a self,
+        address: Address,
+        ws_client: Arc<Provider<Ws>>,
+    ) -> Pin<Box<dyn Future<Output = Option<ContractInfo>> + Send + 'a>>;
+}
+
+/// Try each provider named in `VERIFIED_SOURCE_PROVIDERS` (comma-separated, default
+/// `"blockscout"`) in order, returning the first one that has a verified source for `address`.
+/// Returns an empty `ContractInfo` if every configured provider comes back empty.
+pub async fn fetch_verified_source(address: Address, ws_client: Arc<Provider<Ws>>) -> ContractInfo {
+    for provider in configured_providers() {
+        if let Some(info) = provider.fetch(address, ws_client.clone()).await {
+            return info;
+        }
+    }
+    debug!(
+        "No verified source found for 0x{:x} across configured providers",
+        address
+    );
+    ContractInfo::new()
+}
+
+/// Parse `VERIFIED_SOURCE_PROVIDERS`, skipping (and warning about) any name that isn't a known
+/// backend rather than failing the whole chain over a typo in config.
+fn configured_providers() -> Vec<Box<dyn VerifiedSourceProvider>> {
+    let names = env::var("VERIFIED_SOURCE_PROVIDERS").unwrap_or_else(|_| "blockscout".to_string());
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .filter_map(|name| match name.to_lowercase().as_str() {
+            "blockscout" => Some(Box::new(BlockscoutProvider) as Box<dyn VerifiedSourceProvider>),
+            "sourcify" => Some(Box::new(SourcifyProvider) as Box<dyn VerifiedSourceProvider>),
+            "etherscan" => Some(Box::new(EtherscanProvider) as Box<dyn VerifiedSourceProvider>),
+            other => {
+                warn!("Unknown verified source provider '{}', skipping", other);
+                None
+            }
+        })
+        .collect()
+}
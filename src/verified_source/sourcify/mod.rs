@@ -0,0 +1,112 @@
+// Module: verified_source::sourcify
+//
+// Sourcify publishes verified metadata at `/files/any/{chainId}/{address}`, returning a `files`
+// array of `{name, path, content}` entries rather than Blockscout's flat JSON object — the ABI
+// and compiler settings live inside the `metadata.json` entry's `content`, itself a JSON string.
+
+use crate::indexer_types::{self, ContractType};
+use crate::verified_source::VerifiedSourceProvider;
+use ethers::prelude::*;
+use log::{debug, error as log_error};
+use reqwest::Client;
+use serde_json::Value;
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+pub struct SourcifyProvider;
+
+impl VerifiedSourceProvider for SourcifyProvider {
+    fn fetch<'a>(
+        &'a self,
+        address: Address,
+        ws_client: Arc<Provider<Ws>>,
+    ) -> Pin<Box<dyn Future<Output = Option<indexer_types::ContractInfo>> + Send + 'a>> {
+        Box::pin(fetch(address, ws_client))
+    }
+}
+
+async fn fetch(address: Address, ws_client: Arc<Provider<Ws>>) -> Option<indexer_types::ContractInfo> {
+    let endpoint = env::var("SOURCIFY_ENDPOINT").unwrap_or_else(|_| "https://sourcify.dev/server".to_string());
+    let chain_id = env::var("CHAIN_ID").ok()?;
+    let address_str = format!("0x{:x}", address);
+
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .unwrap();
+    let url = format!("{}/files/any/{}/{}", endpoint, chain_id, address_str);
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            log_error!("Error requesting Sourcify metadata for {}: {}", address_str, e);
+            return None;
+        }
+    };
+    if !response.status().is_success() {
+        debug!("No verified source code found on Sourcify for {}", address_str);
+        return None;
+    }
+    let json: Value = match response.json().await {
+        Ok(json) => json,
+        Err(e) => {
+            log_error!("Error parsing Sourcify response for {}: {}", address_str, e);
+            return None;
+        }
+    };
+
+    let files = json["files"].as_array()?;
+    let metadata_content = files
+        .iter()
+        .find(|file| file["name"].as_str() == Some("metadata.json"))?
+        .get("content")?
+        .as_str()?;
+    let metadata: Value = serde_json::from_str(metadata_content).ok()?;
+
+    let source_code = files
+        .iter()
+        .filter(|file| file["name"].as_str() != Some("metadata.json"))
+        .map(|file| file["content"].as_str().unwrap_or("").to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let abi_json = metadata["output"]["abi"].clone();
+    let res = indexer_types::ContractInfo {
+        contractType: ContractType::detect_contract_type(abi_json.clone(), address, ws_client)
+            .await
+            .to_string(),
+        abi: abi_json.to_string(),
+        abi_json,
+        additionalSources: String::from(""),
+        compilerSettings: metadata["settings"].clone().to_string(),
+        compilerVersion: metadata["compiler"]["version"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        constructorArguments: String::from(""),
+        contractName: metadata["settings"]["compilationTarget"]
+            .as_object()
+            .and_then(|targets| targets.values().next())
+            .and_then(|name| name.as_str())
+            .unwrap_or("")
+            .to_string(),
+        EVMVersion: metadata["settings"]["evmVersion"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        fileName: String::from(""),
+        isProxy: false,
+        optimizationUsed: metadata["settings"]["optimizer"]["enabled"]
+            .as_bool()
+            .unwrap_or(false),
+        sourceCode: source_code,
+    };
+
+    if res.is_null() {
+        return None;
+    }
+
+    debug!("Got verified source code from Sourcify for {}", address_str);
+    Some(res)
+}